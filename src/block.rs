@@ -5,7 +5,6 @@ pub struct Block {
 
 impl Block {
     pub fn new(location: (i32, i32)) -> Self {
-        let empty_block = Block {location};
-        empty_block
+        Block { location }
     }
 }
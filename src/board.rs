@@ -0,0 +1,204 @@
+use crate::color::Color;
+
+pub const GARBAGE_COLOR: Color = Color::from_rgba(130, 130, 130, 255);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Cell {
+    pub color: Color,
+    /// came in as garbage rather than from a locked piece
+    pub garbage: bool,
+}
+
+impl Cell {
+    pub fn garbage() -> Self {
+        Self { color: GARBAGE_COLOR, garbage: true }
+    }
+}
+
+/// the locked stack, y 0 is the top visible row and the buffer sits at negative y
+#[derive(Clone, Debug)]
+pub struct Board {
+    width: usize,
+    visible_height: usize,
+    buffer_height: usize,
+    rows: Vec<Vec<Option<Cell>>>,
+}
+
+impl Board {
+    /// `buffer_height` rows above the visible field are where pieces spawn
+    pub fn new(width: usize, visible_height: usize, buffer_height: usize) -> Self {
+        Self {
+            width,
+            visible_height,
+            buffer_height,
+            rows: vec![vec![None; width]; visible_height + buffer_height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn visible_height(&self) -> usize {
+        self.visible_height
+    }
+
+    pub fn buffer_height(&self) -> usize {
+        self.buffer_height
+    }
+
+    /// every y on the board from the top of the buffer to the floor
+    pub fn rows_range(&self) -> std::ops::Range<i32> {
+        -(self.buffer_height as i32)..self.visible_height as i32
+    }
+
+    // row index for a y coordinate, None when it's off the board
+    fn row_index(&self, y: i32) -> Option<usize> {
+        let index = y + self.buffer_height as i32;
+        if (0..self.rows.len() as i32).contains(&index) {
+            Some(index as usize)
+        } else {
+            None
+        }
+    }
+
+    // (row, column) for a coordinate, None when it's off the board
+    fn index(&self, x: i32, y: i32) -> Option<(usize, usize)> {
+        let row = self.row_index(y)?;
+        if (0..self.width as i32).contains(&x) {
+            Some((row, x as usize))
+        } else {
+            None
+        }
+    }
+
+    pub fn get(&self, x: i32, y: i32) -> Option<Cell> {
+        let (row, column) = self.index(x, y)?;
+        self.rows[row][column]
+    }
+
+    /// filled cells and anything off the board (walls, floor) count as occupied
+    pub fn is_occupied(&self, x: i32, y: i32) -> bool {
+        match self.index(x, y) {
+            Some((row, column)) => self.rows[row][column].is_some(),
+            None => true,
+        }
+    }
+
+    pub fn set(&mut self, x: i32, y: i32, cell: Option<Cell>) {
+        if let Some((row, column)) = self.index(x, y) {
+            self.rows[row][column] = cell;
+        }
+    }
+
+    pub fn is_row_full(&self, y: i32) -> bool {
+        match self.row_index(y) {
+            Some(row) => self.rows[row].iter().all(|cell| cell.is_some()),
+            None => false,
+        }
+    }
+
+    /// finds every full row, takes them all out in one go and drops the rest of the stack down
+    /// to fill the gaps, returns the y of each cleared row as it was before clearing, top first
+    pub fn clear_full_rows(&mut self) -> Vec<i32> {
+        let full: Vec<i32> = self.rows_range().filter(|&y| self.is_row_full(y)).collect();
+
+        self.rows.retain(|row| row.iter().any(|cell| cell.is_none()));
+        for _ in 0..full.len() {
+            self.rows.insert(0, vec![None; self.width]);
+        }
+
+        full
+    }
+
+    /// pushes the whole stack up a row and puts `row` in at the floor,
+    /// true if that pushed anything off the top of the buffer
+    pub fn push_row(&mut self, row: Vec<Option<Cell>>) -> bool {
+        let top = self.rows.remove(0);
+        self.rows.push(row);
+        top.iter().any(|cell| cell.is_some())
+    }
+
+    pub fn has_garbage(&self, y: i32) -> bool {
+        match self.row_index(y) {
+            Some(row) => self.rows[row].iter().flatten().any(|cell| cell.garbage),
+            None => false,
+        }
+    }
+
+    /// how many rows still have garbage in them
+    pub fn garbage_rows(&self) -> usize {
+        self.rows_range().filter(|&y| self.has_garbage(y)).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.iter().all(|row| row.iter().all(|cell| cell.is_none()))
+    }
+
+    /// every filled cell with its (x, y)
+    pub fn cells(&self) -> impl Iterator<Item = ((i32, i32), Cell)> + '_ {
+        (-(self.buffer_height as i32)..).zip(self.rows.iter()).flat_map(|(y, row)| {
+            (0..).zip(row.iter()).filter_map(move |(x, cell)| cell.map(|cell| ((x, y), cell)))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fill_row(board: &mut Board, y: i32, hole: Option<i32>) {
+        for x in 0..board.width() as i32 {
+            if Some(x) != hole {
+                board.set(x, y, Some(Cell::garbage()));
+            }
+        }
+    }
+
+    #[test]
+    fn clears_rows_that_are_not_next_to_each_other() {
+        let mut board = Board::new(4, 6, 2);
+        fill_row(&mut board, 5, None);
+        fill_row(&mut board, 4, Some(1));
+        fill_row(&mut board, 3, None);
+        board.set(2, 2, Some(Cell::garbage()));
+
+        assert_eq!(board.clear_full_rows(), vec![3, 5]);
+
+        // the gapped row drops two and the lone block above it drops one
+        assert_eq!(board.get(1, 5), None);
+        assert!((0..4).filter(|&x| x != 1).all(|x| board.is_occupied(x, 5)));
+        assert!(board.is_occupied(2, 4));
+        assert_eq!(board.cells().count(), 4);
+        assert!(!board.is_row_full(5));
+    }
+
+    #[test]
+    fn clearing_everything_leaves_an_empty_board() {
+        let mut board = Board::new(4, 6, 2);
+        fill_row(&mut board, 5, None);
+        fill_row(&mut board, 2, None);
+
+        assert_eq!(board.clear_full_rows(), vec![2, 5]);
+        assert!(board.is_empty());
+    }
+
+    #[test]
+    fn nothing_full_clears_nothing() {
+        let mut board = Board::new(4, 6, 2);
+        fill_row(&mut board, 5, Some(0));
+
+        assert!(board.clear_full_rows().is_empty());
+        assert_eq!(board.cells().count(), 3);
+    }
+
+    #[test]
+    fn push_row_reports_blocks_pushed_off_the_top() {
+        let mut board = Board::new(4, 2, 1);
+        assert!(!board.push_row(vec![Some(Cell::garbage()); 4]));
+        assert!(board.is_row_full(1));
+
+        board.set(0, -1, Some(Cell::garbage()));
+        assert!(board.push_row(vec![None; 4]));
+    }
+}
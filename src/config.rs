@@ -0,0 +1,109 @@
+use crate::input::Handling;
+use crate::mode::GameMode;
+use crate::randomizer::RandomizerKind;
+use crate::scoring::ScoreTable;
+
+pub const MAX_PREVIEW: usize = 6;
+/// narrowest board the I piece fits across flat
+pub const MIN_WIDTH: usize = 4;
+/// shortest field the I piece fits down upright
+pub const MIN_VISIBLE_HEIGHT: usize = 4;
+/// pieces spawn in the two rows above the visible field
+pub const MIN_BUFFER_HEIGHT: usize = 2;
+/// biggest any side of the board can be, keeps a bad config from allocating a huge board
+pub const MAX_BOARD_SIZE: usize = 1000;
+/// highest level a game can start on, gravity stops changing at 20 anyway
+pub const MAX_START_LEVEL: u32 = 99;
+/// biggest value any score table entry can have
+pub const MAX_POINTS: u32 = 100_000;
+
+/// what buys a grounded piece more time before it locks
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LockReset {
+    /// every move or rotation restarts the timer
+    Infinite,
+    /// moves and rotations restart the timer this many times, falling to a new lowest row gives them back
+    MoveReset(u32),
+    /// only falling to a new lowest row restarts the timer
+    StepReset,
+}
+
+impl Default for LockReset {
+    fn default() -> Self {
+        Self::MoveReset(15)
+    }
+}
+
+/// everything about a game that's picked before it starts
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GameConfig {
+    pub mode: GameMode,
+    pub randomizer: RandomizerKind,
+    /// how many upcoming pieces are shown, 1 to MAX_PREVIEW
+    pub preview_count: usize,
+    pub scoring: ScoreTable,
+    pub start_level: u32,
+    /// milliseconds a piece can sit on the stack before it locks
+    pub lock_delay: f64,
+    pub lock_reset: LockReset,
+    pub width: usize,
+    pub visible_height: usize,
+    /// hidden rows above the visible field that pieces spawn into
+    pub buffer_height: usize,
+    pub handling: Handling,
+    /// chance from 0 to 1 that each garbage row's hole moves from the row below, 0 digs one clean column
+    pub garbage_messiness: f64,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            mode: GameMode::default(),
+            randomizer: RandomizerKind::default(),
+            preview_count: 5,
+            scoring: ScoreTable::GUIDELINE,
+            start_level: 1,
+            lock_delay: 500.0,
+            lock_reset: LockReset::default(),
+            width: 10,
+            visible_height: 20,
+            buffer_height: 20,
+            handling: Handling::default(),
+            garbage_messiness: 1.0,
+        }
+    }
+}
+
+impl GameConfig {
+    /// the config a game actually gets played with, everything pulled into a range the engine
+    /// can run: boards big enough to spawn on and no bigger than MAX_BOARD_SIZE, a preview of
+    /// 1 to MAX_PREVIEW, a start level and score table that can't get near overflowing,
+    /// and no negative or NaN times
+    pub fn normalized(mut self) -> Self {
+        self.preview_count = self.preview_count.clamp(1, MAX_PREVIEW);
+        self.start_level = self.start_level.min(MAX_START_LEVEL);
+
+        let table = &mut self.scoring;
+        for points in table.lines.iter_mut()
+            .chain(table.tspin.iter_mut())
+            .chain(table.tspin_mini.iter_mut())
+            .chain(table.perfect_clear.iter_mut())
+            .chain([&mut table.combo, &mut table.back_to_back, &mut table.soft_drop, &mut table.hard_drop])
+        {
+            *points = (*points).min(MAX_POINTS);
+        }
+
+        self.width = self.width.clamp(MIN_WIDTH, MAX_BOARD_SIZE);
+        self.visible_height = self.visible_height.clamp(MIN_VISIBLE_HEIGHT, MAX_BOARD_SIZE);
+        self.buffer_height = self.buffer_height.clamp(MIN_BUFFER_HEIGHT, MAX_BOARD_SIZE);
+
+        // f64::max throws away NaN as well as anything negative
+        self.lock_delay = self.lock_delay.max(0.0);
+        self.handling.das = self.handling.das.max(0.0);
+        self.handling.arr = self.handling.arr.max(0.0);
+        self.handling.dcd = self.handling.dcd.max(0.0);
+        self.handling.sdf = self.handling.sdf.max(0.0);
+        self.garbage_messiness = self.garbage_messiness.max(0.0).clamp(0.0, 1.0);
+        self
+    }
+}
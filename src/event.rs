@@ -0,0 +1,25 @@
+use crate::game_handler::{GameOverReason, LineClearEvent, MoveDirection};
+use crate::piece::PieceEnum;
+use crate::rotation::{RotateDirection, Rotation};
+use crate::scoring::TSpin;
+
+/// something that happened inside the game, collected until the front-end drains them
+#[derive(Debug, Clone, PartialEq)]
+pub enum GameEvent {
+    PieceSpawned(PieceEnum),
+    Moved(MoveDirection),
+    Rotated { direction: RotateDirection, rotation: Rotation },
+    /// the rotation only fit after moving, `kick` is the index into the kick table
+    Kicked { kick: usize, offset: (i32, i32) },
+    Locked(PieceEnum),
+    LinesCleared(LineClearEvent),
+    TSpin(TSpin),
+    /// the piece that went into hold
+    HoldUsed(PieceEnum),
+    LevelUp(u32),
+    /// the mode's line goal got cleared, the game only keeps going in endless marathon
+    GoalReached,
+    /// rows of garbage pushed in under the stack
+    GarbageAdded(u32),
+    GameOver(GameOverReason),
+}
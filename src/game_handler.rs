@@ -1,68 +1,155 @@
-use macroquad::time::get_time;
+use std::collections::VecDeque;
 
 use crate::piece::{Piece, PieceEnum};
-use crate::piece::FindOpen;
 
 use crate::block::Block;
-
-const QUARTER: f64 = std::f64::consts::TAU/4.0; // 90 degrees as radians
-
+use crate::board::{Board, Cell};
+use crate::config::{GameConfig, LockReset, MIN_VISIBLE_HEIGHT};
+use crate::event::GameEvent;
+use crate::garbage::Garbage;
+use crate::input::{InputHandler, Inputs};
+use crate::level::{frames_per_row, frames_to_ms, level_for_lines, FPS, FRAME_TIME};
+use crate::mode::{GameMode, DIG_ROWS_SHOWN, SPLIT_LINES, SURVIVAL_MIN_INTERVAL, SURVIVAL_START_INTERVAL};
+use crate::randomizer::Randomizer;
+use crate::rng::Rng;
+use crate::rotation::{kicks, RotateDirection, Rotation};
+use crate::scoring::{Scorer, TSpin};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum MoveDirection {
     Left, 
     Right,
     Down,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GameOverReason {
+    /// a new piece spawned on top of the stack
+    BlockOut,
+    /// a piece locked with every block above the visible field
+    LockOut,
+    /// garbage pushed the stack out the top of the board
+    TopOut,
+    /// the mode's line goal got cleared
+    GoalReached,
+    /// the mode's time limit ran out
+    TimeUp,
+}
+
+impl GameOverReason {
+    /// false when the game ended because the player got where the mode wanted
+    pub fn is_loss(&self) -> bool {
+        match self {
+            Self::BlockOut | Self::LockOut | Self::TopOut => true,
+            Self::GoalReached | Self::TimeUp => false,
+        }
+    }
+}
+
+/// what a single lock cleared, handed back even when nothing did
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct LineClearEvent {
+    /// y of each cleared row from before the clear, top first
+    pub rows: Vec<i32>,
+    pub tspin: TSpin,
+    pub perfect_clear: bool,
+    /// how many of the cleared rows had garbage in them
+    pub garbage: usize,
+}
+
+impl LineClearEvent {
+    pub fn lines(&self) -> usize {
+        self.rows.len()
+    }
+}
+
 #[derive(Debug)]
 pub struct Game {
     pub inplay: Piece,
-    pub playfield: Vec<Piece>,
-    border: Vec<Block>,
+    pub board: Board,
     pub score: u32,
-    bag: [PieceEnum; 8],
-    bag_i: usize,
-    pub lost: bool,
-    //pub timer: f64,
-    pub timer: f64,
+    pub level: u32,
+    pub lines: u32,
+    pub mode: GameMode,
+    /// frame every SPLIT_LINES lines were reached on
+    pub splits: Vec<u64>,
+    start_level: u32,
+    // rows of gravity built up but not fallen yet
+    fall: f64,
+    lock_delay: f64,
+    lock_reset: LockReset,
+    // ms spent on the ground, resets used and the lowest row reached by the piece in play
+    lock_timer: f64,
+    lock_resets: u32,
+    lowest_row: i32,
+    pub scorer: Scorer,
+    // spin the piece in play locked with, worked out before it goes into the board
+    lock_tspin: TSpin,
+    // direction and kick index while the last thing that moved the piece was a rotation
+    last_rotation: Option<(RotateDirection, usize)>,
+    queue: VecDeque<PieceEnum>,
+    pub hold: Option<PieceEnum>,
+    hold_used: bool,
+    randomizer: Box<dyn Randomizer>,
+    rng: Rng,
+    garbage: Garbage,
+    // dig rows that haven't come up onto the board yet
+    garbage_queued: u32,
+    // survival's next garbage row comes in on this frame, with the gap before the one after
+    next_garbage: u64,
+    garbage_interval: u64,
+    pub game_over: Option<GameOverReason>,
+    events: Vec<GameEvent>,
+    /// ticks simulated so far, 60 a second
+    pub frame: u64,
+    input: InputHandler,
+    last_inputs: Inputs,
 }
 
 impl Game {
-    pub fn rotate_piece(&mut self) {
-        let center = self.inplay.center; 
-        let mut new_shape: Vec<Block> = Vec::new(); 
-
-        let mut collision = false;
-        for block in self.inplay.shape.iter_mut() {
-            let (old_x, old_y) = block.location;
-
-            if (old_x == center.0) && (old_y == center.1) {
-                new_shape.push(Block::new(center));
-                continue; // the center doesnt have to be rotated
-            }
-
-            let new_x = ((old_x-center.0) as f64*QUARTER.cos())-((old_y-center.1) as f64*QUARTER.sin());
-            let new_y = ((old_x-center.0) as f64*QUARTER.sin())-((old_y-center.1) as f64*QUARTER.cos());
-            let result = (new_x.round() as i32+center.0, new_y.round() as i32+center.1);
-
-            for piece in self.playfield.iter() {
-                for block in piece.shape.iter() {
-                    if block.location == result {
-                        collision = true;
-                    }
+    /// srs rotation, returns which kick got used (0 = none needed) or None if every kick collided
+    pub fn rotate_piece(&mut self, direction: RotateDirection) -> Option<usize> {
+        let pivot = self.inplay.pivot;
+        let from = self.inplay.rotation;
+        let to = from.rotate(direction);
+
+        // rotation is done in half cells about the pivot, y points down so clockwise is (x, y) -> (-y, x)
+        let rotated: Vec<(i32, i32)> = self.inplay.shape.iter().map(|block| {
+            let (x, y) = (block.location.0*2-pivot.0, block.location.1*2-pivot.1);
+            let (x, y) = match direction {
+                RotateDirection::Clockwise => (-y, x),
+                RotateDirection::CounterClockwise => (y, -x),
+                RotateDirection::Half => (-x, -y),
+            };
+            ((x+pivot.0)/2, (y+pivot.1)/2)
+        }).collect();
+
+        for (i, kick) in kicks(self.inplay.piece_enum, from, to).into_iter().enumerate() {
+            let collision = rotated.iter()
+                .any(|&(x, y)| self.collides((x+kick.0, y+kick.1)));
+
+            if !collision {
+                self.inplay.shape = rotated.iter()
+                    .map(|&(x, y)| Block::new((x+kick.0, y+kick.1)))
+                    .collect();
+                self.inplay.pivot = (pivot.0+kick.0*2, pivot.1+kick.1*2);
+                self.inplay.rotation = to;
+                self.last_rotation = Some((direction, i));
+                self.reset_lock();
+
+                self.events.push(GameEvent::Rotated { direction, rotation: to });
+                if i > 0 {
+                    self.events.push(GameEvent::Kicked { kick: i, offset: kick });
                 }
+                return Some(i);
             }
+        }
 
-            for block in self.border.iter() {
-                if block.location == result {
-                    collision = true;
-                }
-            }
-            new_shape.push(Block::new(result));
-        } 
+        None
+    }
 
-        if collision == false {
-            self.inplay.shape = new_shape;
-        }
+    fn collides(&self, location: (i32, i32)) -> bool {
+        self.board.is_occupied(location.0, location.1)
     }
 
     /// false = no collision, true = collision
@@ -73,195 +160,624 @@ impl Game {
             MoveDirection::Down => (0, 1),
         };
         let mut new_shape: Vec<Block> = Vec::new();
-        let new_center = (self.inplay.center.0+move_vector.0, self.inplay.center.1+move_vector.1);
+        let new_pivot = (self.inplay.pivot.0+move_vector.0*2, self.inplay.pivot.1+move_vector.1*2);
         
         let mut collision = false;
-        for block in self.inplay.shape.iter_mut() {
+        for block in self.inplay.shape.iter() {
             let new_location = (block.location.0+move_vector.0, block.location.1+move_vector.1);
 
-            for piece in self.playfield.iter() {
-                for block in piece.shape.iter() {
-                    if block.location == new_location {
-                        collision = true;
-                    }
-                }
+            if self.collides(new_location) {
+                collision = true;
+            }
+            new_shape.push(Block::new(new_location));
+        }
+        if !collision {
+            self.inplay.shape = new_shape;
+            self.inplay.pivot = new_pivot;
+            self.last_rotation = None;
+
+            match direction {
+                MoveDirection::Down => self.step_lock(),
+                _ => self.reset_lock(),
             }
+            self.events.push(GameEvent::Moved(direction));
+        }
+
+        collision
+    }
 
-            for block in self.border.iter() {
-                if block.location == new_location {
-                    collision = true;
+    /// where the piece in play would end up after a hard drop
+    pub fn ghost_position(&self) -> Vec<Block> {
+        let mut drop = 0;
+        while !self.inplay.shape.iter().any(|block| self.collides((block.location.0, block.location.1+drop+1))) {
+            drop += 1;
+        }
+
+        self.inplay.shape.iter()
+            .map(|block| Block::new((block.location.0, block.location.1+drop)))
+            .collect()
+    }
+
+    fn grounded(&self) -> bool {
+        self.inplay.shape.iter()
+            .any(|block| self.collides((block.location.0, block.location.1+1)))
+    }
+
+    // a move or rotation worked, buy more time if the reset rule allows it
+    fn reset_lock(&mut self) {
+        match self.lock_reset {
+            LockReset::Infinite => self.lock_timer = 0.0,
+            LockReset::MoveReset(limit) => {
+                // resets only get used up while the piece is actually locking
+                if self.lock_timer > 0.0 && self.lock_resets < limit {
+                    self.lock_timer = 0.0;
+                    self.lock_resets += 1;
                 }
+            },
+            LockReset::StepReset => (),
+        }
+    }
+
+    // the piece fell a row, a new lowest row starts the lock over
+    fn step_lock(&mut self) {
+        let bottom = self.inplay.shape.iter().map(|block| block.location.1).max().unwrap_or(i32::MIN);
+        if bottom > self.lowest_row {
+            self.lowest_row = bottom;
+            self.lock_timer = 0.0;
+            self.lock_resets = 0;
+        }
+    }
+
+    /// one fixed 1/60 s step with whatever buttons are held, the only thing that moves time forward
+    pub fn tick(mut self, inputs: Inputs) -> Self {
+        if self.is_over() {
+            return self;
+        }
+        if let Some(seconds) = self.mode.time_limit() && self.frame >= seconds as u64*FPS as u64 {
+            self.end(GameOverReason::TimeUp);
+            return self;
+        }
+        if self.mode == GameMode::Survival && self.frame >= self.next_garbage {
+            // each row comes a twentieth sooner than the last
+            self.garbage_interval = (self.garbage_interval*19/20).max(SURVIVAL_MIN_INTERVAL);
+            self.next_garbage += self.garbage_interval;
+            self.add_garbage(1);
+            if self.is_over() {
+                return self;
             }
-            new_shape.push(Block::new(new_location));
+        }
+
+        let pressed = inputs.pressed_since(self.last_inputs);
+        self.last_inputs = inputs;
+        self.frame += 1;
 
+        if pressed.contains(Inputs::HOLD) {
+            self = self.hold_piece();
+            self.input.cut();
+            // holding can spawn straight into the stack
+            if self.is_over() {
+                return self;
+            }
         }
-        if collision == false {
-            self.inplay.shape = new_shape;
-            self.inplay.center = new_center;
+
+        let rotations = [
+            (Inputs::ROTATE_CW, RotateDirection::Clockwise),
+            (Inputs::ROTATE_CCW, RotateDirection::CounterClockwise),
+            (Inputs::ROTATE_180, RotateDirection::Half),
+        ];
+        for (button, direction) in rotations {
+            if pressed.contains(button) {
+                self.rotate_piece(direction);
+                self.input.cut();
+            }
         }
 
-        return collision;
+        // the handler needs the game to move the piece, so it steps out for a moment
+        let mut input = std::mem::take(&mut self.input);
+        input.update(&mut self, inputs.into(), FRAME_TIME);
+        self.input = input;
+
+        if pressed.contains(Inputs::HARD_DROP) {
+            // hard drop skips the lock delay
+            self.hard_drop();
+            self.input.cut();
+            return self.lock_piece();
+        }
 
+        if self.update(FRAME_TIME) {
+            self = self.lock_piece();
+        }
+        self
     }
 
-    pub fn check_lose(&mut self) {
-        for piece in self.playfield.iter() {
-            for block in piece.shape.iter() {
-                if block.location.1 < 0 {
-                    //return true;
-                    self.lost = true;
-                    return;
-                }
+    // moves gravity and lock delay along by `dt` seconds,
+    // true once the piece has sat on the stack long enough and should be placed
+    fn update(&mut self, dt: f64) -> bool {
+        self.fall += dt*FPS/frames_per_row(self.level);
+        while self.fall >= 1.0 {
+            self.fall -= 1.0;
+            if self.move_piece(MoveDirection::Down) {
+                self.fall = 0.0;
+                break;
             }
-            
         }
-        //return false;
+
+        if !self.grounded() {
+            return false;
+        }
+
+        if let LockReset::MoveReset(limit) = self.lock_reset {
+            // out of resets, it locks the moment it touches down
+            if self.lock_resets >= limit {
+                return true;
+            }
+        }
+
+        self.lock_timer += dt*1000.0;
+        self.lock_timer >= self.lock_delay
     }
 
-    pub fn move_board_down(&mut self, if_above: i32) {
-        for piece in self.playfield.iter_mut() {
-            for block in piece.shape.iter_mut() {
-                if block.location.1 <= if_above {
-                    block.location = (block.location.0, block.location.1+1);
+    /// place, clear lines and bring in the next piece, what happens whenever a piece locks
+    pub fn lock_piece(mut self) -> Self {
+        // nothing gets written into the board once the game has ended
+        if self.is_over() {
+            return self;
+        }
+        self.place_piece();
+        self.check_line();
+
+        if !self.is_over() {
+            self.spawn_next();
+            self.hold_used = false;
+        }
+
+        self
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.game_over.is_some()
+    }
+
+    /// pushes `rows` garbage rows in under the stack, topping out if that shoves blocks off the board
+    pub fn add_garbage(&mut self, rows: u32) {
+        if rows == 0 {
+            return;
+        }
+        self.events.push(GameEvent::GarbageAdded(rows));
+
+        for _ in 0..rows {
+            let row = self.garbage.next_row(self.board.width());
+            if self.board.push_row(row) {
+                self.end(GameOverReason::TopOut);
+                return;
+            }
+
+            // the piece in play rides up with the stack rather than ending up inside it
+            if self.inplay.shape.iter().any(|block| self.collides(block.location)) {
+                for block in self.inplay.shape.iter_mut() {
+                    block.location.1 -= 1;
                 }
+                self.inplay.pivot.1 -= 2;
+                self.lowest_row = self.lowest_row.saturating_sub(1);
             }
         }
     }
 
-    pub fn remove_line(&mut self, line: i32) {
-        for piece in self.playfield.iter_mut() {
-            piece.shape.retain(|element| {element.location.1 != line});
+    // how many dig rows go on the board at once, short fields keep MIN_VISIBLE_HEIGHT rows clear to play in
+    fn dig_rows_shown(&self) -> u32 {
+        let room = self.board.visible_height().saturating_sub(MIN_VISIBLE_HEIGHT).max(1);
+        DIG_ROWS_SHOWN.min(room as u32)
+    }
+
+    /// dig rows still to clear, on the board or yet to come up
+    pub fn garbage_left(&self) -> u32 {
+        self.board.garbage_rows() as u32 + self.garbage_queued
+    }
+
+    /// ticks until survival pushes up its next row, None in every other mode
+    pub fn garbage_countdown(&self) -> Option<u64> {
+        match self.mode {
+            GameMode::Survival => Some(self.next_garbage.saturating_sub(self.frame)),
+            _ => None,
+        }
+    }
+
+    /// lines this game is won at, if its mode has a goal
+    pub fn line_goal(&self) -> Option<u32> {
+        self.mode.line_goal(self.start_level)
+    }
+
+    /// game time so far, stops when the game ends
+    pub fn time_ms(&self) -> u64 {
+        frames_to_ms(self.frame)
+    }
+
+    /// one row down, worth soft drop points if it moved
+    pub fn soft_drop(&mut self) -> bool {
+        let collision = self.move_piece(MoveDirection::Down);
+        if !collision {
+            self.score = self.score.saturating_add(self.scorer.soft_drop(1));
         }
+        collision
     }
 
-    pub fn check_line(&mut self) {
-        let mut piece_map: Vec<Vec<bool>> = vec![vec![false; 10]; 24];
+    /// drops as far as it goes and returns how many rows that was
+    pub fn hard_drop(&mut self) -> u32 {
+        let mut rows = 0;
+        while !self.move_piece(MoveDirection::Down) {
+            rows += 1;
+        }
+        self.score = self.score.saturating_add(self.scorer.hard_drop(rows));
+        rows
+    }
+
+    pub fn check_line(&mut self) -> LineClearEvent {
+        let garbage = self.board.rows_range()
+            .filter(|&y| self.board.is_row_full(y) && self.board.has_garbage(y))
+            .count();
+        let rows = self.board.clear_full_rows();
+        let event = LineClearEvent {
+            perfect_clear: !rows.is_empty() && self.board.is_empty(),
+            rows,
+            tspin: self.lock_tspin,
+            garbage,
+        };
+
+        let points = self.scorer.lock(event.lines(), event.tspin, event.perfect_clear, self.level);
+        self.score = self.score.saturating_add(points);
+        self.lines = self.lines.saturating_add(event.lines() as u32);
+        let level = level_for_lines(self.start_level, self.lines);
 
+        if event.tspin != TSpin::None {
+            self.events.push(GameEvent::TSpin(event.tspin));
+        }
+        if event.lines() > 0 {
+            self.events.push(GameEvent::LinesCleared(event.clone()));
+        }
 
-        for piece in self.playfield.iter() {
-            for block in piece.shape.iter() {
-                piece_map[(block.location.1+4) as usize][(block.location.0) as usize] = true;
+        // one clear can cross a split line, a tetris going 8 to 12 still gets its split at 10
+        while (self.splits.len() as u32 + 1)*SPLIT_LINES <= self.lines {
+            self.splits.push(self.frame);
+        }
+        let before = self.lines - event.lines() as u32;
+        let goal_reached = self.line_goal().is_some_and(|goal| before < goal && self.lines >= goal);
+        if goal_reached && self.mode.ends_at_goal() {
+            // the winning clear finishes the last level rather than starting another
+            self.events.push(GameEvent::GoalReached);
+            self.end(GameOverReason::GoalReached);
+        } else {
+            if level > self.level {
+                self.events.push(GameEvent::LevelUp(level));
             }
-        } 
-
-        let mut cline = -4;
-        for line in piece_map.iter() {
-            let mut line_clear = true;
-            for element in line.iter() {
-                if *element == false {
-                    line_clear = false;
-                }
+            self.level = level;
+            if goal_reached {
+                self.events.push(GameEvent::GoalReached);
             }
+        }
+
+        if let GameMode::Dig { .. } = self.mode {
+            // keep the board topped up from the rows still to come
+            let missing = self.dig_rows_shown().saturating_sub(self.board.garbage_rows() as u32);
+            let rows = missing.min(self.garbage_queued);
+            self.garbage_queued -= rows;
+            self.add_garbage(rows);
 
-            if line_clear == true {
-                self.remove_line(cline);
-                self.move_board_down(cline);
-                self.score += 100;
-                //return true;
+            if self.garbage_left() == 0 {
+                self.events.push(GameEvent::GoalReached);
+                self.end(GameOverReason::GoalReached);
             }
-            cline += 1;
         }
-        //return false;
+
+        event
+    }
+
+    /// two games with the same seed get the same pieces in the same order
+    pub fn with_seed(seed: u64) -> Self {
+        Self::with_config(seed, GameConfig::default())
     }
 
-    pub fn new_game() -> Self {
-        let mut border: Vec<Block> = Vec::new();
-        for y in -4..=19 {
-            let block_left = Block::new((-1, y));
-            let block_right = Block::new((10, y));
-            border.push(block_left);
-            border.push(block_right);
+    /// `config` gets normalized first, so the game is always one a replay can record
+    pub fn with_config(seed: u64, config: GameConfig) -> Self {
+        let config = config.normalized();
+        let board = Board::new(config.width, config.visible_height, config.buffer_height);
+        let mut rng = Rng::new(seed);
+        let mut randomizer = config.randomizer.build();
+        let first = randomizer.next_piece(&mut rng);
+        let queue = (0..config.preview_count).map(|_| randomizer.next_piece(&mut rng)).collect();
+
+        let mut game = Self {
+            inplay: Piece::new(&first, board.width()),
+            board,
+            score: 0,
+            level: config.start_level,
+            lines: 0,
+            mode: config.mode,
+            splits: Vec::new(),
+            start_level: config.start_level,
+            fall: 0.0,
+            lock_delay: config.lock_delay,
+            lock_reset: config.lock_reset,
+            lock_timer: 0.0,
+            lock_resets: 0,
+            lowest_row: i32::MIN,
+            scorer: Scorer::new(config.scoring),
+            lock_tspin: TSpin::None,
+            last_rotation: None,
+            queue,
+            hold: None,
+            hold_used: false,
+            randomizer,
+            rng,
+            garbage: Garbage::new(seed, config.garbage_messiness),
+            garbage_queued: 0,
+            next_garbage: SURVIVAL_START_INTERVAL,
+            garbage_interval: SURVIVAL_START_INTERVAL,
+            game_over: None,
+            events: Vec::new(),
+            frame: 0,
+            input: InputHandler::new(config.handling),
+            last_inputs: Inputs::default(),
+        };
+
+        if let GameMode::Dig { rows } = config.mode {
+            let shown = rows.min(game.dig_rows_shown());
+            game.garbage_queued = rows - shown;
+            game.add_garbage(shown);
         }
 
-        for x in 0..=9 {
-            let block = Block::new((x, 20));
-            border.push(block);
+        // the opening piece goes through spawn like every other, so it gets the block out check too
+        game.spawn(&first);
+        game
+    }
+
+    pub fn next_piece(&self) -> PieceEnum {
+        self.queue[0]
+    }
+
+    /// the whole preview, soonest first
+    pub fn next_pieces(&self) -> impl Iterator<Item = &PieceEnum> {
+        self.queue.iter()
+    }
+
+    fn end(&mut self, reason: GameOverReason) {
+        if self.game_over.is_none() {
+            self.game_over = Some(reason);
+            self.events.push(GameEvent::GameOver(reason));
         }
+    }
 
-        let bag = PieceEnum::generate_bag(None);
+    /// everything that's happened since the last call, oldest first,
+    /// events are kept until they're drained so anything driving a game should call this every tick
+    pub fn drain_events(&mut self) -> Vec<GameEvent> {
+        std::mem::take(&mut self.events)
+    }
 
-        Self { 
-            inplay: Piece::new(&bag[0]), 
-            playfield: Vec::new(), 
-            border: border, 
-            score: 0, 
-            bag: bag,
-            bag_i: 1,
-            lost: false,
-            //timer: 0.0,
-            timer: get_time(),
+    // writes the piece in play into the board, the piece stays in play until spawn_next
+    fn place_piece(&mut self) {
+        self.lock_tspin = self.check_tspin();
+        self.events.push(GameEvent::Locked(self.inplay.piece_enum));
+        for block in self.inplay.shape.iter() {
+            let (x, y) = block.location;
+            self.board.set(x, y, Some(Cell { color: self.inplay.color, garbage: false }));
+        }
+
+        if self.inplay.shape.iter().all(|block| block.location.1 < 0) {
+            self.end(GameOverReason::LockOut);
         }
     }
 
-    pub fn next_piece(&mut self) -> PieceEnum {
-        let next_piece = if self.bag_i >= 7 {
-            let next_piece = self.bag[self.bag_i];
-            self.bag = PieceEnum::generate_bag(Some(self.bag[7]));
-            self.bag_i = 1;
-            next_piece
-        } else {
-            self.bag[self.bag_i]
+    // 3 corner rule, walls and floor count as filled
+    fn check_tspin(&self) -> TSpin {
+        let (direction, kick) = match self.last_rotation {
+            Some(rotation) if self.inplay.piece_enum == PieceEnum::T => rotation,
+            _ => return TSpin::None,
         };
-        next_piece
-    }
 
-    pub fn place_piece(mut self) -> Self {
-        let inplay = self.inplay;
-        self.playfield.push(inplay);
+        let (x, y) = (self.inplay.pivot.0/2, self.inplay.pivot.1/2);
+        let filled = |(dx, dy): (i32, i32)| self.collides((x+dx, y+dy)) as usize;
 
-        let next_piece = if self.bag_i >= 7 {
-            let next_piece = self.bag[self.bag_i];
-            self.bag = PieceEnum::generate_bag(Some(self.bag[7]));
-            self.bag_i = 1;
-            next_piece
-        } else {
-            self.bag[self.bag_i]
+        let corners = filled((-1, -1)) + filled((1, -1)) + filled((-1, 1)) + filled((1, 1));
+        if corners < 3 {
+            return TSpin::None;
+        }
+
+        // the two corners either side of where the t points
+        let front = match self.inplay.rotation {
+            Rotation::Spawn => [(-1, -1), (1, -1)],
+            Rotation::Right => [(1, -1), (1, 1)],
+            Rotation::Two => [(-1, 1), (1, 1)],
+            Rotation::Left => [(-1, -1), (-1, 1)],
         };
-        
-        self.inplay = Piece::new(&next_piece);
-        self.bag_i += 1;
 
-        return self;
+        // the last srs kick always counts as a full spin
+        let last_kick = direction != RotateDirection::Half && kick == 4;
+        if filled(front[0]) + filled(front[1]) == 2 || last_kick {
+            TSpin::Full
+        } else {
+            TSpin::Mini
+        }
+    }
+
+    // pulls the next piece out of the queue and tops the queue back up, returns the old piece
+    fn spawn_next(&mut self) -> Piece {
+        let next = self.queue.pop_front().unwrap();
+        self.queue.push_back(self.randomizer.next_piece(&mut self.rng));
+        self.spawn(&next)
+    }
+
+    // puts a fresh piece in play with nothing carried over from the last one
+    fn spawn(&mut self, piece_enum: &PieceEnum) -> Piece {
+        self.last_rotation = None;
+        self.fall = 0.0;
+        self.lock_timer = 0.0;
+        self.lock_resets = 0;
+        self.lowest_row = i32::MIN;
+        let old = std::mem::replace(&mut self.inplay, Piece::new(piece_enum, self.board.width()));
+        self.events.push(GameEvent::PieceSpawned(*piece_enum));
+
+        if self.inplay.shape.iter().any(|block| self.collides(block.location)) {
+            self.end(GameOverReason::BlockOut);
+        }
+        old
     }
 
-    //[J, O, S, I, T, L, Z, I], 6 //place
-    //[J, O, S, I, T, L, Z, I], 7
-    //[I, L, Z, S, J, O, T, S], 1 //borrow next one turns into what we currently have therefore we
-    //must check to randomize the currently existing one we already have
-    //[L, Z, Z, S, J, O, T, S], 1
+    /// false once hold has been used for the piece in play
+    pub fn can_hold(&self) -> bool {
+        !self.hold_used
+    }
 
-    // there's gotta be a better way to do this
+    // one hold per piece, it unlocks again once a piece gets placed
     pub fn hold_piece(mut self) -> Self {
+        if self.hold_used {
+            return self;
+        }
+
         let current_piece = self.inplay.piece_enum;
+        let held = self.hold.replace(current_piece);
+        self.events.push(GameEvent::HoldUsed(current_piece));
+        self.hold_used = true;
+
+        // the spawn comes last so a block out is the final thing that happens
+        match held {
+            Some(held) => {
+                self.spawn(&held);
+            },
+            None => {
+                self.spawn_next();
+            },
+        }
 
-        let next_piece = self.next_piece();
+        self
+    }
+}
 
-        self.bag[self.bag_i-1] = next_piece;
-        self.bag[self.bag_i] = current_piece;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        if self.bag_i == 1 {
-            let mut none = 0;
-            for i in 2..=6 {
-                if self.bag[i] == self.bag[1] {
-                    self.bag[i] = PieceEnum::None;
-                    none = i;
-                    break;
-                }
+    fn fill_row(game: &mut Game, y: i32, hole: Option<i32>, cell: Cell) {
+        for x in 0..game.board.width() as i32 {
+            if Some(x) != hole {
+                game.board.set(x, y, Some(cell));
             }
+        }
+    }
 
-            let slice = &self.bag[1..=6];
-            let open = slice.find_open();
-            let set_piece = match open.first() {
-                None => panic!(),
-                Some(piece) => piece,
-            };
+    fn stack() -> Cell {
+        Cell { color: PieceEnum::I.color(), garbage: false }
+    }
+
+    fn marathon(start_level: u32, endless: bool) -> Game {
+        let config = GameConfig { mode: GameMode::Marathon { endless }, start_level, ..GameConfig::default() };
+        Game::with_config(1, config)
+    }
+
+    #[test]
+    fn line_clears_list_rows_top_first_with_their_garbage() {
+        let mut game = Game::with_seed(1);
+        fill_row(&mut game, 19, None, Cell::garbage());
+        fill_row(&mut game, 18, Some(3), Cell::garbage());
+        fill_row(&mut game, 17, None, stack());
+        game.drain_events();
+
+        let event = game.check_line();
+        assert_eq!(event.rows, vec![17, 19]);
+        assert_eq!(event.garbage, 1);
+        assert!(!event.perfect_clear);
+        assert_eq!(game.lines, 2);
+        assert!(game.drain_events().contains(&GameEvent::LinesCleared(event)));
+    }
+
+    #[test]
+    fn clearing_the_whole_stack_is_a_perfect_clear() {
+        let mut game = Game::with_seed(1);
+        fill_row(&mut game, 19, None, stack());
+
+        let event = game.check_line();
+        assert_eq!(event.rows, vec![19]);
+        assert_eq!(event.garbage, 0);
+        assert!(event.perfect_clear);
+
+        // a lock that clears nothing still hands back an empty event, and nothing to show for it
+        game.drain_events();
+        assert_eq!(game.check_line(), LineClearEvent::default());
+        assert!(game.drain_events().is_empty());
+    }
 
-            self.bag[none] = *set_piece;
+    // a t turned to point right and dropped down column 4, its pivot ends up at (4, 18)
+    fn drop_right_facing_t(game: &mut Game) {
+        game.inplay = Piece::new(&PieceEnum::T, game.board.width());
+        game.rotate_piece(RotateDirection::Clockwise);
+        while !game.move_piece(MoveDirection::Down) {}
+    }
+
+    #[test]
+    fn tsd_in_a_covered_slot_is_a_full_spin() {
+        let mut game = Game::with_seed(1);
+        fill_row(&mut game, 19, Some(4), stack());
+        for x in (0..10).filter(|x| !(3..=5).contains(x)) {
+            game.board.set(x, 18, Some(stack()));
         }
+        game.board.set(3, 17, Some(stack()));
 
-        self.inplay = Piece::new(&self.bag[self.bag_i-1]);
+        drop_right_facing_t(&mut game);
+        assert_eq!(game.rotate_piece(RotateDirection::Clockwise), Some(0));
+        let mut game = game.lock_piece();
 
-        return self;
+        assert_eq!(game.score, 1200);
+        assert_eq!(game.lines, 2);
+        assert!(game.drain_events().contains(&GameEvent::TSpin(TSpin::Full)));
     }
 
+    #[test]
+    fn a_spin_with_one_front_corner_is_a_mini() {
+        let mut game = Game::with_seed(1);
+        drop_right_facing_t(&mut game);
+        // both back corners and one of the two the t points at
+        for (x, y) in [(3, 17), (5, 17), (3, 19)] {
+            game.board.set(x, y, Some(stack()));
+        }
+
+        game.rotate_piece(RotateDirection::Clockwise);
+        let game = game.lock_piece();
+        assert_eq!(game.score, 100);
+        assert_eq!(game.scorer.combo, None);
+    }
+
+    #[test]
+    fn marathon_is_won_by_finishing_level_15() {
+        let mut game = marathon(5, false);
+        assert_eq!(game.line_goal(), Some(110));
+
+        game.lines = 108;
+        game.level = 15;
+        fill_row(&mut game, 19, None, Cell::garbage());
+        fill_row(&mut game, 18, None, Cell::garbage());
+        game.check_line();
+
+        // the winning clear ends the game on level 15 instead of starting 16
+        let events = game.drain_events();
+        assert_eq!(game.game_over, Some(GameOverReason::GoalReached));
+        assert_eq!(game.level, 15);
+        assert!(!events.iter().any(|event| matches!(event, GameEvent::LevelUp(_))));
+        assert!(events.contains(&GameEvent::GoalReached));
+    }
+
+    #[test]
+    fn endless_marathon_levels_up_past_the_goal() {
+        let mut game = marathon(1, true);
+        assert_eq!(game.line_goal(), Some(150));
+        assert_eq!(marathon(20, true).line_goal(), Some(10));
+
+        game.lines = 148;
+        game.level = 15;
+        fill_row(&mut game, 19, None, Cell::garbage());
+        fill_row(&mut game, 18, None, Cell::garbage());
+        game.check_line();
+
+        let events = game.drain_events();
+        assert_eq!(game.game_over, None);
+        assert_eq!(game.level, 16);
+        assert_eq!(&events[events.len() - 2..], [GameEvent::LevelUp(16), GameEvent::GoalReached]);
+    }
 }
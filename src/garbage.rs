@@ -0,0 +1,39 @@
+use crate::board::Cell;
+use crate::rng::Rng;
+
+/// makes garbage rows with one hole each, off its own rng so garbage doesn't change the pieces dealt
+#[derive(Debug, Clone)]
+pub struct Garbage {
+    rng: Rng,
+    /// chance from 0 to 1 that a row's hole moves away from the one below it
+    messiness: f64,
+    hole: Option<usize>,
+}
+
+impl Garbage {
+    pub fn new(seed: u64, messiness: f64) -> Self {
+        Self {
+            // a different stream from the randomizer even with the same seed
+            rng: Rng::new(seed ^ 0x6761_7262_6167_6521),
+            messiness,
+            hole: None,
+        }
+    }
+
+    pub fn next_row(&mut self, width: usize) -> Vec<Option<Cell>> {
+        let width = width as u32;
+        let hole = match self.hole {
+            // a single column has nowhere else for the hole to go
+            Some(hole) if width <= 1 || self.rng.next_f64() >= self.messiness => hole,
+            // a hole that moves always lands in a different column
+            Some(hole) => {
+                let column = self.rng.gen_range(0, width - 1) as usize;
+                if column >= hole { column + 1 } else { column }
+            },
+            None => self.rng.gen_range(0, width) as usize,
+        };
+        self.hole = Some(hole);
+
+        (0..width as usize).map(|x| if x == hole { None } else { Some(Cell::garbage()) }).collect()
+    }
+}
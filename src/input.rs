@@ -0,0 +1,197 @@
+use crate::game_handler::{Game, MoveDirection};
+use crate::level::{frames_per_row, FPS};
+
+/// per player tuning, times are in milliseconds
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Handling {
+    /// delayed auto shift, how long left or right has to be held before it repeats
+    pub das: f64,
+    /// auto repeat rate, time between repeats once das kicks in, 0 goes straight to the wall
+    pub arr: f64,
+    /// das cut delay, repeating pauses this long after a rotation, hold or hard drop
+    pub dcd: f64,
+    /// soft drop factor, how many times faster than gravity soft drop falls, infinity drops to the floor
+    pub sdf: f64,
+}
+
+impl Default for Handling {
+    fn default() -> Self {
+        Self {
+            das: 167.0,
+            arr: 33.0,
+            dcd: 0.0,
+            sdf: 20.0,
+        }
+    }
+}
+
+/// every button held down during one tick, one bit each so a tick fits in a u16
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Inputs(pub u16);
+
+impl Inputs {
+    pub const LEFT: Self = Self(1 << 0);
+    pub const RIGHT: Self = Self(1 << 1);
+    pub const SOFT_DROP: Self = Self(1 << 2);
+    pub const HARD_DROP: Self = Self(1 << 3);
+    pub const ROTATE_CW: Self = Self(1 << 4);
+    pub const ROTATE_CCW: Self = Self(1 << 5);
+    pub const ROTATE_180: Self = Self(1 << 6);
+    pub const HOLD: Self = Self(1 << 7);
+
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// buttons in `self` that weren't held in `last`
+    pub fn pressed_since(self, last: Self) -> Self {
+        Self(self.0 & !last.0)
+    }
+}
+
+impl std::ops::BitOr for Inputs {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+}
+
+impl std::ops::BitOrAssign for Inputs {
+    fn bitor_assign(&mut self, other: Self) {
+        self.0 |= other.0;
+    }
+}
+
+/// what's held down this frame
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct HeldKeys {
+    pub left: bool,
+    pub right: bool,
+    pub soft_drop: bool,
+}
+
+impl From<Inputs> for HeldKeys {
+    fn from(inputs: Inputs) -> Self {
+        Self {
+            left: inputs.contains(Inputs::LEFT),
+            right: inputs.contains(Inputs::RIGHT),
+            soft_drop: inputs.contains(Inputs::SOFT_DROP),
+        }
+    }
+}
+
+/// turns held keys into moves with das, arr and soft drop factor
+#[derive(Debug, Clone, Default)]
+pub struct InputHandler {
+    pub handling: Handling,
+    last: HeldKeys,
+    // the direction that's charging, the last one pressed wins when both are held
+    direction: Option<MoveDirection>,
+    das_timer: f64,
+    arr_timer: f64,
+    dcd_timer: f64,
+    // rows of soft drop built up but not fallen yet
+    soft_drop: f64,
+}
+
+impl InputHandler {
+    pub fn new(handling: Handling) -> Self {
+        Self { handling, ..Default::default() }
+    }
+
+    /// call after a rotation, hold or hard drop so das cut delay can pause repeating
+    pub fn cut(&mut self) {
+        self.dcd_timer = self.handling.dcd;
+    }
+
+    pub fn update(&mut self, game: &mut Game, held: HeldKeys, dt: f64) {
+        let ms = dt*1000.0;
+
+        if held.left && !self.last.left {
+            self.start(game, MoveDirection::Left);
+        } else if held.right && !self.last.right {
+            self.start(game, MoveDirection::Right);
+        } else {
+            // let go of the charging key, fall back to the other one if it's still down
+            match self.direction {
+                Some(MoveDirection::Left) if !held.left => self.fall_back(game, held.right, MoveDirection::Right),
+                Some(MoveDirection::Right) if !held.right => self.fall_back(game, held.left, MoveDirection::Left),
+                Some(direction) => self.repeat(game, direction, ms),
+                None => (),
+            }
+        }
+        self.last = held;
+
+        self.soft_drop(game, held.soft_drop, dt);
+    }
+
+    fn start(&mut self, game: &mut Game, direction: MoveDirection) {
+        game.move_piece(direction);
+        self.direction = Some(direction);
+        self.das_timer = 0.0;
+        self.arr_timer = 0.0;
+    }
+
+    fn fall_back(&mut self, game: &mut Game, other_held: bool, other: MoveDirection) {
+        if other_held {
+            self.start(game, other);
+        } else {
+            self.direction = None;
+        }
+    }
+
+    fn repeat(&mut self, game: &mut Game, direction: MoveDirection, ms: f64) {
+        if self.dcd_timer > 0.0 {
+            self.dcd_timer -= ms;
+            return;
+        }
+
+        let charged = self.das_timer >= self.handling.das;
+        self.das_timer += ms;
+        if self.das_timer < self.handling.das {
+            return;
+        }
+
+        if self.handling.arr <= 0.0 {
+            while !game.move_piece(direction) {}
+            return;
+        }
+
+        if charged {
+            self.arr_timer += ms;
+        } else {
+            // das just ran out, that's worth a move straight away
+            game.move_piece(direction);
+            self.arr_timer = self.das_timer-self.handling.das;
+        }
+
+        while self.arr_timer >= self.handling.arr {
+            self.arr_timer -= self.handling.arr;
+            if game.move_piece(direction) {
+                break;
+            }
+        }
+    }
+
+    fn soft_drop(&mut self, game: &mut Game, held: bool, dt: f64) {
+        if !held {
+            self.soft_drop = 0.0;
+            return;
+        }
+
+        if self.handling.sdf.is_infinite() {
+            while !game.soft_drop() {}
+            return;
+        }
+
+        self.soft_drop += dt*FPS/frames_per_row(game.level)*self.handling.sdf;
+        while self.soft_drop >= 1.0 {
+            self.soft_drop -= 1.0;
+            if game.soft_drop() {
+                self.soft_drop = 0.0;
+                break;
+            }
+        }
+    }
+}
@@ -0,0 +1,32 @@
+pub const FPS: f64 = 60.0;
+/// seconds per tick, the simulation always steps by exactly this much
+pub const FRAME_TIME: f64 = 1.0/FPS;
+pub const LINES_PER_LEVEL: u32 = 10;
+
+/// from level 20 on pieces fall 20 rows a frame, they hit the stack as soon as they spawn
+pub const TWENTY_G: f64 = 1.0/20.0;
+
+// frames per row for levels 1 to 19, (0.8 - (level - 1) * 0.007)^(level - 1) seconds from the guideline
+const GRAVITY: [f64; 19] = [
+    60.0, 47.58, 37.07, 28.36, 21.31, 15.72, 11.38, 8.08, 5.63, 3.85,
+    2.58, 1.69, 1.09, 0.69, 0.42, 0.26, 0.15, 0.09, 0.05,
+];
+
+/// how many frames it takes to fall one row, under 1 means more than a row per frame
+pub fn frames_per_row(level: u32) -> f64 {
+    match level {
+        0 => GRAVITY[0],
+        1..=19 => GRAVITY[level as usize - 1],
+        _ => TWENTY_G,
+    }
+}
+
+/// game time for a number of ticks, what sprint and ultra timers show
+pub fn frames_to_ms(frames: u64) -> u64 {
+    frames*1000/FPS as u64
+}
+
+/// fixed goal, a level every 10 lines on top of where the game started
+pub fn level_for_lines(start_level: u32, lines: u32) -> u32 {
+    start_level.saturating_add(lines/LINES_PER_LEVEL)
+}
@@ -0,0 +1,33 @@
+// headless game engine, the macroquad front-end lives in main.rs
+
+pub mod block;
+pub mod board;
+pub mod color;
+pub mod config;
+pub mod event;
+pub mod game_handler;
+pub mod garbage;
+pub mod input;
+pub mod level;
+pub mod mode;
+pub mod piece;
+pub mod randomizer;
+pub mod replay;
+pub mod rng;
+pub mod rotation;
+pub mod scoring;
+pub mod stats;
+
+pub use board::{Board, Cell};
+pub use garbage::Garbage;
+pub use config::{GameConfig, LockReset};
+pub use event::GameEvent;
+pub use game_handler::{Game, GameOverReason, LineClearEvent, MoveDirection};
+pub use input::{Handling, HeldKeys, InputHandler, Inputs};
+pub use mode::GameMode;
+pub use piece::{Piece, PieceEnum};
+pub use randomizer::{Randomizer, RandomizerKind};
+pub use replay::{Replay, ReplayError, ReplayPlayer};
+pub use rotation::{RotateDirection, Rotation};
+pub use scoring::{ScoreTable, TSpin};
+pub use stats::Stats;
@@ -1,18 +1,32 @@
 #![windows_subsystem = "windows"]
 
-mod game_handler;
+mod playback;
 mod rect;
-mod piece;
-mod block;
 
 use std::time::{SystemTime, UNIX_EPOCH};
 
-use game_handler::{Game, MoveDirection};
-use macroquad::{audio::{load_sound_from_bytes, play_sound, PlaySoundParams}, prelude::*, rand::srand};
+use macroquad::{audio::{load_sound_from_bytes, play_sound, stop_sound, PlaySoundParams}, prelude::*};
 use macroquad::time::get_time;
+use tetris::{Game, GameConfig, GameEvent, GameMode, GameOverReason, Inputs, PieceEnum, Replay, Stats, TSpin};
+use tetris::level::{frames_to_ms, FPS, FRAME_TIME};
+use tetris::mode::{DIG_ROWS, MARATHON_LEVEL, SPLIT_LINES, SPRINT_DEFAULT, SPRINT_GOALS, ULTRA_DEFAULT, ULTRA_LIMITS};
+
+use playback::Playback;
+use rect::Rect;
 
 const GREY: Color = GRAY; // i refuse to spell grey that way
 
+const KEYS: [(KeyCode, Inputs); 8] = [
+    (KeyCode::A, Inputs::LEFT),
+    (KeyCode::D, Inputs::RIGHT),
+    (KeyCode::S, Inputs::SOFT_DROP),
+    (KeyCode::Space, Inputs::HARD_DROP),
+    (KeyCode::W, Inputs::ROTATE_CW),
+    (KeyCode::Q, Inputs::ROTATE_CCW),
+    (KeyCode::E, Inputs::ROTATE_180),
+    (KeyCode::C, Inputs::HOLD),
+];
+
 fn config() -> Conf {
     Conf {
         window_title: "Tetris".to_string(),
@@ -32,110 +46,440 @@ fn config() -> Conf {
 async fn main() {
     let korbeiniki = load_sound_from_bytes(include_bytes!("resources/korobeiniki.wav")).await.unwrap();
 
-    let sound_params = PlaySoundParams {
-        looped: true,
-        volume: 1.0,
-    };
-    play_sound(&korbeiniki, sound_params);
-
     let mut font = load_ttf_font_from_bytes(include_bytes!("resources/font.ttf")).unwrap();
     font.set_filter(FilterMode::Nearest);
 
-    if cfg!(target_family = "wasm") {
-        srand((get_time() * 10000.0) as u64);
-    } else {
-        srand(SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs());
-    }
+    let mut config = GameConfig::default();
+    // every tick of the game in progress, so it can be saved and watched back
+    let (mut game, mut replay) = new_game(config);
+    let mut stats = Stats::default();
+    let mut popups = Popups::default();
 
-    let mut game = Game::new_game();
+    // a replay file passed on the command line gets watched straight away
+    let mut playback = std::env::args().nth(1).and_then(|path| Playback::load(&path));
+    // (mode, variant) highlighted on the mode select, None once a game is going
+    let modes = modes();
+    let mut menu = if playback.is_some() { None } else { Some((0, modes[0].default)) };
+
+    // real time not simulated yet, and taps that came and went before a tick could see them
+    let mut accumulator = 0.0;
+    let mut taps = Inputs::default();
 
     'top_level: loop {
         clear_background(BLACK);
-        
+
+        if let Some((mut selected, mut variant)) = menu {
+            let mut start = false;
+            for key in get_keys_pressed() {
+                let variants = modes[selected].variants.len();
+                match key {
+                    KeyCode::Up | KeyCode::W => {
+                        selected = (selected + modes.len() - 1) % modes.len();
+                        variant = modes[selected].default;
+                    },
+                    KeyCode::Down | KeyCode::S => {
+                        selected = (selected + 1) % modes.len();
+                        variant = modes[selected].default;
+                    },
+                    KeyCode::Left | KeyCode::A => variant = (variant + variants - 1) % variants,
+                    KeyCode::Right | KeyCode::D => variant = (variant + 1) % variants,
+                    KeyCode::Enter => start = true,
+                    KeyCode::Escape => break 'top_level,
+                    _ => (),
+                }
+            }
+
+            draw_menu(&font, &modes, selected, variant);
+
+            if start {
+                config.mode = modes[selected].variants[variant].1;
+                (game, replay) = new_game(config);
+                stats = Stats::default();
+                popups = Popups::default();
+                accumulator = 0.0;
+                taps = Inputs::default();
+                play_sound(&korbeiniki, music_params());
+                menu = None;
+            } else {
+                menu = Some((selected, variant));
+            }
+
+            next_frame().await;
+            continue;
+        }
+
+        if let Some(mut watching) = playback.take() {
+            let mut stop = false;
+            for key in get_keys_pressed() {
+                match key {
+                    KeyCode::Enter => stop = true,
+                    KeyCode::Escape => break 'top_level,
+                    key => watching = watching.handle_key(key),
+                }
+            }
+
+            watching = watching.update(get_frame_time() as f64);
+            for event in std::mem::take(&mut watching.events) {
+                popups.record(&event);
+            }
+
+            draw_game(&watching.player.game, &font, &popups);
+            watching.draw_overlay(&font);
+
+            if stop {
+                // back to picking a game of our own
+                popups = Popups::default();
+                menu = Some((0, modes[0].default));
+            } else {
+                playback = Some(watching);
+            }
+
+            next_frame().await;
+            continue;
+        }
+
         for key in get_keys_pressed() {
             match key {
-                KeyCode::A => {game.move_piece(MoveDirection::Left);},
-                KeyCode::D => {game.move_piece(MoveDirection::Right);},
-                KeyCode::W => {game.rotate_piece();},
-                KeyCode::S => {game.move_piece(MoveDirection::Down);},
-                KeyCode::Space => {
-                    'drop: loop {
-                        if game.move_piece(MoveDirection::Down) {
-                            break 'drop;
-                        }
-                    }
+                KeyCode::Enter if game.is_over() => {
+                    (game, replay) = new_game(config);
+                    stats = Stats::default();
+                    popups = Popups::default();
+                    play_sound(&korbeiniki, music_params());
                 },
-                KeyCode::C => game = game.hold_piece(),
-                KeyCode::Enter => {
-                    if game.lost {
-                        game = Game::new_game();
-                    }
+                KeyCode::M if game.is_over() => {
+                    popups = Popups::default();
+                    menu = modes.iter().enumerate().find_map(|(i, menu_mode)| {
+                        menu_mode.variants.iter().position(|(_, mode)| *mode == config.mode).map(|variant| (i, variant))
+                    });
+                },
+                KeyCode::R if game.is_over() => {
+                    popups = Popups::default();
+                    playback = Some(Playback::new(replay.clone()));
                 },
                 KeyCode::Escape => break 'top_level,
-                _ => (), 
+                _ => (),
             }
         }
 
-        for piece in game.playfield.iter() {
-            for block in piece.shape.iter() {
-                let (x, y) = block.location; 
-                let (x, y) = (x as f32, y as f32);
-                draw_rectangle(x*60.0, y*60.0, 60.0, 60.0, piece.color);
+        let mut held = Inputs::default();
+        for (key, button) in KEYS {
+            if is_key_down(key) {
+                held |= button;
+            }
+            if is_key_pressed(key) {
+                taps |= button;
             }
         }
 
-        for block in game.inplay.shape.iter() {
-                let (x, y) = block.location; 
-                let (x, y) = (x as f32, y as f32);
-                draw_rectangle(x*60.0, y*60.0, 60.0, 60.0, game.inplay.color);
+        // never try to catch up on more than a quarter second, say after the window was dragged
+        accumulator = (accumulator + get_frame_time() as f64).min(0.25);
+        while accumulator >= FRAME_TIME {
+            if !game.is_over() {
+                replay.record(held | taps);
+            }
+            game = game.tick(held | taps);
+            taps = Inputs::default();
+            accumulator -= FRAME_TIME;
         }
 
-        for x in 0..=10 {
-            draw_rectangle((x as f32*60.0)-1.0, 0.0, 2.0,1200.0, GREY);
+        for event in game.drain_events() {
+            stats.record(&event);
+            popups.record(&event);
+            if let GameEvent::GameOver(_) = event {
+                stop_sound(&korbeiniki);
+                playback::save(&replay);
+            }
         }
 
-        for y in 0..=20 {
-            draw_rectangle(0.0, (y as f32*60.0)-1.0, 600.0,2.0, GREY);
+        draw_game(&game, &font, &popups);
+
+        if game.is_over() {
+            clear_background(BLACK);
+            draw_game_over(&font, &game, &stats);
         }
 
-        let next_piece = game.next_piece().as_rects();
-        for rect in next_piece.0.iter() {
-            let (x, y, w, h) = rect.get_data();
-            draw_rectangle(x, y, w, h, next_piece.1);
+        next_frame().await
+    }
+}
+
+// text raised by game events, with when it showed up
+#[derive(Default)]
+struct Popups {
+    // like "T-SPIN DOUBLE", next to the score
+    clear: Option<(String, f64)>,
+    // level ups and goals, over the board
+    notice: Option<(String, f64)>,
+}
+
+impl Popups {
+    fn record(&mut self, event: &GameEvent) {
+        let now = get_time();
+        match event {
+            // a spin without lines only shows up as a TSpin event
+            GameEvent::TSpin(tspin) => if let Some(text) = clear_text(*tspin, 0) {
+                self.clear = Some((text, now));
+            },
+            GameEvent::LinesCleared(clear) => if let Some(text) = clear_text(clear.tspin, clear.lines()) {
+                self.clear = Some((text, now));
+            },
+            GameEvent::LevelUp(level) => self.notice = Some((format!("LEVEL {level}"), now)),
+            GameEvent::GoalReached => self.notice = Some(("GOAL CLEARED".to_string(), now)),
+            _ => (),
         }
+    }
+}
 
-        let interval = (1000.0-((1.0/11.0)*game.score as f64))/1000.0;
-        if get_time()-game.timer > interval && (!game.lost) {
-            let collision = game.move_piece(MoveDirection::Down);
-            if collision {
-                game = game.place_piece();
-                game.check_lose();
-                game.check_line();
-            }
-            game.timer = get_time();
+fn draw_game(game: &Game, font: &Font, popups: &Popups) {
+    // the board gets the left half of the window whatever its size
+    let (width, height) = (game.board.width(), game.board.visible_height());
+    let size = (600.0/width as f32).min(1200.0/height as f32);
+
+    for ((x, y), cell) in game.board.cells() {
+        let (x, y) = (x as f32, y as f32);
+        draw_rectangle(x*size, y*size, size, size, mq_color(cell.color));
+    }
+
+    let mut ghost_color = mq_color(game.inplay.color);
+    ghost_color.a = 0.25;
+    for block in game.ghost_position().iter() {
+            let (x, y) = block.location;
+            let (x, y) = (x as f32, y as f32);
+            draw_rectangle(x*size, y*size, size, size, ghost_color);
+    }
+
+    for block in game.inplay.shape.iter() {
+            let (x, y) = block.location; 
+            let (x, y) = (x as f32, y as f32);
+            draw_rectangle(x*size, y*size, size, size, mq_color(game.inplay.color));
+    }
+
+    for x in 0..=width {
+        draw_rectangle((x as f32*size)-1.0, 0.0, 2.0, height as f32*size, GREY);
+    }
+
+    for y in 0..=height {
+        draw_rectangle(0.0, (y as f32*size)-1.0, width as f32*size, 2.0, GREY);
+    }
+
+    for (i, next_piece) in game.next_pieces().enumerate() {
+        draw_preview(next_piece, 1050, 430 + i as u32*120, 40, mq_color(next_piece.color()));
+    }
+
+    if let Some(held) = game.hold {
+        // greyed out until the piece in play gets placed
+        let color = if game.can_hold() { mq_color(held.color()) } else { GREY };
+        draw_preview(&held, 750, 430, 40, color);
+    }
+
+    text_helper(font, 100, 900.0, 100.0, "SCORE");
+    text_helper(font, 100, 900.0, 200.0, &game.score.to_string());
+    text_helper(font, 60, 900.0, 270.0, &format!("LEVEL {}", game.level));
+
+    if let Some((text, shown)) = &popups.clear && get_time()-shown < 2.0 {
+        text_helper(font, 50, 900.0, 320.0, text);
+    }
+
+    if let Some((text, shown)) = &popups.notice && get_time()-shown < 1.5 {
+        let (board_width, board_height) = (width as f32*size, height as f32*size);
+        draw_rectangle(0.0, board_height/2.0-60.0, board_width, 120.0, Color::new(0.0, 0.0, 0.0, 0.75));
+        text_helper(font, 80, board_width/2.0, board_height/2.0, text);
+    }
+
+    text_helper(font, 90, 750.0, 380.0, "HOLD");
+    text_helper(font, 90, 1050.0, 380.0, "NEXT");
+
+    if let GameMode::Marathon { endless } = game.mode && let Some(goal) = game.line_goal() {
+        let lines = if endless && game.lines >= goal {
+            format!("LINES {}", game.lines)
+        } else {
+            format!("LINES {}/{goal}", game.lines)
+        };
+        text_helper(font, 50, 750.0, 620.0, &lines);
+    }
+
+    if let GameMode::Sprint { lines: goal } = game.mode {
+        text_helper(font, 50, 750.0, 620.0, &format_time(game.time_ms()));
+        text_helper(font, 50, 750.0, 690.0, &format!("{}/{goal}", game.lines));
+
+        // only the latest few fit under hold
+        let first = game.splits.len().saturating_sub(5);
+        for (i, &frame) in game.splits.iter().enumerate().skip(first) {
+            let split = format!("{}  {}", (i as u32 + 1)*SPLIT_LINES, format_time(frames_to_ms(frame)));
+            text_helper(font, 36, 750.0, 760.0 + (i - first) as f32*45.0, &split);
         }
-        
-        text_helper(&font, 128, 900.0, 150.0, "SCORE");
-        text_helper(&font, 128, 900.0, 250.0, &game.score.to_string());
+    }
 
-        text_helper(&font, 90, 900.0, 650.0, "NEXT PIECE");
+    if let GameMode::Dig { .. } = game.mode {
+        text_helper(font, 50, 750.0, 620.0, &format_time(game.time_ms()));
+        text_helper(font, 50, 750.0, 690.0, &format!("GARBAGE {}", game.garbage_left()));
+    }
 
-        if game.lost {
-            clear_background(BLACK);
-            text_helper(&font, 200, 600.0, 200.0, "YOU  LOST");
+    if let Some(countdown) = game.garbage_countdown() {
+        text_helper(font, 50, 750.0, 620.0, &format_time(game.time_ms()));
+        text_helper(font, 50, 750.0, 690.0, &format!("RISE {:.1}", countdown as f64 / FPS));
+    }
 
-            text_helper(&font, 120, 600.0, 600.0, "TRY AGAIN");
-            text_helper(&font, 120, 600.0, 700.0, "ENTER");
+    if let Some(seconds) = game.mode.time_limit() {
+        // counts down, rounding up so it only reads 0:00 once time is up
+        let left = (seconds as u64*1000).saturating_sub(game.time_ms());
+        text_helper(font, 50, 750.0, 620.0, &format_clock(left.div_ceil(1000)));
+        text_helper(font, 50, 750.0, 690.0, &format!("LINES {}", game.lines));
+    }
+}
 
-            text_helper(&font, 120, 600.0, 900.0, "EXIT");
-            text_helper(&font, 120, 600.0, 1000.0, "ESC");
+fn draw_menu(font: &Font, modes: &[MenuMode], selected: usize, variant: usize) {
+    text_helper(font, 200, 600.0, 200.0, "TETRIS");
+
+    for (i, MenuMode { name, variants, default }) in modes.iter().enumerate() {
+        let y = 400.0 + i as f32*110.0;
+        // everything but the highlighted mode shows its default
+        let shown = if i == selected { variant } else { *default };
+        if i == selected {
+            draw_rectangle(150.0, y-45.0, 900.0, 90.0, DARKGRAY);
         }
 
-        next_frame().await
+        text_helper(font, 70, 400.0, y, name);
+        if variants.len() > 1 {
+            text_helper(font, 60, 800.0, y, &format!("<  {}  >", variants[shown].0));
+        }
+    }
+
+    text_helper(font, 50, 600.0, 1050.0, "ARROWS  ENTER");
+    text_helper(font, 50, 600.0, 1110.0, "EXIT  ESC");
+}
+
+fn draw_game_over(font: &Font, game: &Game, stats: &Stats) {
+    match game.game_over {
+        Some(GameOverReason::GoalReached) if matches!(game.mode, GameMode::Marathon { .. }) => {
+            text_helper(font, 150, 600.0, 150.0, "YOU WIN");
+            text_helper(font, 120, 600.0, 290.0, &game.score.to_string());
+
+            text_helper(font, 60, 600.0, 400.0, &format!("LINES {}", game.lines));
+            text_helper(font, 60, 600.0, 470.0, &format!("TIME {}", format_time(game.time_ms())));
+            text_helper(font, 60, 600.0, 540.0, &format!("PIECES {}", stats.pieces));
+        },
+        Some(GameOverReason::GoalReached) => {
+            text_helper(font, 150, 600.0, 150.0, "FINISHED");
+            text_helper(font, 120, 600.0, 290.0, &format_time(game.time_ms()));
+
+            let seconds = game.time_ms() as f64 / 1000.0;
+            let pps = stats.pieces as f64 / seconds.max(0.001);
+            let pieces = format!("{} PIECES  {pps:.2} PPS", stats.pieces);
+            let summary = match game.mode {
+                GameMode::Dig { rows } => format!("{rows} GARBAGE  {pieces}"),
+                _ => pieces,
+            };
+            text_helper(font, 50, 600.0, 380.0, &summary);
+
+            // each split with how long that stretch of lines took, the last 10 fit
+            let first = game.splits.len().saturating_sub(10);
+            let mut last = 0;
+            for (i, &frame) in game.splits.iter().enumerate() {
+                let ms = frames_to_ms(frame);
+                if i >= first {
+                    let split = format!("{}  {}  +{}", (i as u32 + 1)*SPLIT_LINES, format_time(ms), format_time(ms - last));
+                    text_helper(font, 36, 600.0, 450.0 + (i - first) as f32*42.0, &split);
+                }
+                last = ms;
+            }
+        },
+        Some(GameOverReason::TimeUp) => {
+            text_helper(font, 150, 600.0, 150.0, "TIME UP");
+            text_helper(font, 120, 600.0, 290.0, &game.score.to_string());
+
+            let seconds = (game.time_ms() as f64 / 1000.0).max(0.001);
+            text_helper(font, 60, 600.0, 400.0, &format!("LINES {}", game.lines));
+            text_helper(font, 60, 600.0, 470.0, &format!("{:.1} POINTS/S", game.score as f64 / seconds));
+            text_helper(font, 60, 600.0, 540.0, &format!("{:.2} LINES/S", game.lines as f64 / seconds));
+            text_helper(font, 60, 600.0, 610.0, &format!("{:.2} PIECES/S", stats.pieces as f64 / seconds));
+        },
+        Some(reason) => {
+            let reason = match reason {
+                GameOverReason::BlockOut => "BLOCK OUT",
+                GameOverReason::LockOut => "LOCK OUT",
+                GameOverReason::TopOut => "TOP OUT",
+                GameOverReason::GoalReached | GameOverReason::TimeUp => "",
+            };
+
+            // survival always ends in a loss, how long it lasted is the result
+            if game.mode == GameMode::Survival {
+                text_helper(font, 150, 600.0, 150.0, "SURVIVED");
+                text_helper(font, 120, 600.0, 290.0, &format_time(game.time_ms()));
+                text_helper(font, 60, 600.0, 400.0, &format!("LINES DUG {}", stats.dug));
+                text_helper(font, 60, 600.0, 470.0, &format!("PIECES {}", stats.pieces));
+                text_helper(font, 60, 600.0, 540.0, reason);
+            } else {
+                text_helper(font, 200, 600.0, 200.0, "YOU  LOST");
+                text_helper(font, 80, 600.0, 350.0, reason);
+                text_helper(font, 60, 600.0, 450.0, &format!("PIECES {}", stats.pieces));
+            }
+        },
+        None => (),
+    }
+
+    text_helper(font, 60, 600.0, 900.0, "TRY AGAIN  ENTER");
+    text_helper(font, 60, 600.0, 960.0, "MODES  M");
+    text_helper(font, 60, 600.0, 1020.0, "WATCH REPLAY  R");
+    text_helper(font, 60, 600.0, 1080.0, "EXIT  ESC");
+}
+
+// one line of the mode select, left and right flip between its variants
+struct MenuMode {
+    name: &'static str,
+    variants: Vec<(String, GameMode)>,
+    // the variant picked when the cursor lands on the mode
+    default: usize,
+}
+
+impl MenuMode {
+    fn new(name: &'static str, variants: Vec<(String, GameMode)>) -> Self {
+        Self { name, variants, default: 0 }
+    }
+
+    fn with_default(mut self, mode: GameMode) -> Self {
+        self.default = self.variants.iter().position(|(_, variant)| *variant == mode).unwrap_or(0);
+        self
     }
 }
 
-fn text_helper(font: &Font, size: u16, x: f32, y: f32, text: &str) {
+fn modes() -> Vec<MenuMode> {
+    vec![
+        MenuMode::new("MARATHON", vec![
+            (format!("LEVEL {MARATHON_LEVEL}"), GameMode::Marathon { endless: false }),
+            ("ENDLESS".to_string(), GameMode::Marathon { endless: true }),
+        ]),
+        MenuMode::new("SPRINT", SPRINT_GOALS.iter().map(|&lines| (lines.to_string(), GameMode::Sprint { lines })).collect())
+            .with_default(GameMode::Sprint { lines: SPRINT_DEFAULT }),
+        MenuMode::new("ULTRA", ULTRA_LIMITS.iter().map(|&seconds| (format_clock(seconds as u64), GameMode::Ultra { seconds })).collect())
+            .with_default(GameMode::Ultra { seconds: ULTRA_DEFAULT }),
+        MenuMode::new("DIG", DIG_ROWS.iter().map(|&rows| (rows.to_string(), GameMode::Dig { rows })).collect()),
+        MenuMode::new("SURVIVAL", vec![(String::new(), GameMode::Survival)]),
+    ]
+}
+
+fn new_game(config: GameConfig) -> (Game, Replay) {
+    let seed = new_seed();
+    (Game::with_config(seed, config), Replay::new(seed, config))
+}
+
+// m:ss.mmm
+fn format_time(ms: u64) -> String {
+    format!("{}:{:02}.{:03}", ms/60000, ms/1000%60, ms%1000)
+}
+
+// m:ss
+fn format_clock(seconds: u64) -> String {
+    format!("{}:{:02}", seconds/60, seconds%60)
+}
+
+fn music_params() -> PlaySoundParams {
+    PlaySoundParams {
+        looped: true,
+        volume: 1.0,
+    }
+}
+
+pub fn text_helper(font: &Font, size: u16, x: f32, y: f32, text: &str) {
         let text_params = TextParams {
             font_scale: 1.0,
             font_scale_aspect: 1.0,
@@ -148,3 +492,59 @@ fn text_helper(font: &Font, size: u16, x: f32, y: f32, text: &str) {
         let center = get_text_center(text, Some(font), size, 1.0, 0.0);
         draw_text_ex(text, x-center.x, y-center.y, text_params);
 }
+
+// what the last lock was called, None for a plain drop
+fn clear_text(tspin: TSpin, lines: usize) -> Option<String> {
+    let lines = match lines {
+        0 => "",
+        1 => " SINGLE",
+        2 => " DOUBLE",
+        3 => " TRIPLE",
+        _ => "TETRIS",
+    };
+
+    match tspin {
+        TSpin::Full => Some(format!("T-SPIN{lines}")),
+        TSpin::Mini => Some(format!("MINI T-SPIN{lines}")),
+        TSpin::None if !lines.is_empty() => Some(lines.trim().to_string()),
+        TSpin::None => None,
+    }
+}
+
+fn new_seed() -> u64 {
+    if cfg!(target_family = "wasm") {
+        (get_time() * 10000.0) as u64
+    } else {
+        SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos() as u64
+    }
+}
+
+fn mq_color(color: tetris::color::Color) -> Color {
+    Color::from_rgba(color.r, color.g, color.b, color.a)
+}
+
+// shapes in cells, centered on `center` starting at `top`
+fn preview_rects(piece: &PieceEnum, center: u32, top: u32, cell: u32) -> Vec<Rect> {
+    let (width, cells) = match piece {
+        PieceEnum::I => (4, vec![(0, 0, 4, 1)]),
+        PieceEnum::O => (2, vec![(0, 0, 2, 2)]),
+        PieceEnum::T => (3, vec![(1, 0, 1, 1), (0, 1, 3, 1)]),
+        PieceEnum::S => (3, vec![(1, 0, 2, 1), (0, 1, 2, 1)]),
+        PieceEnum::Z => (3, vec![(0, 0, 2, 1), (1, 1, 2, 1)]),
+        PieceEnum::J => (3, vec![(0, 0, 1, 1), (0, 1, 3, 1)]),
+        PieceEnum::L => (3, vec![(2, 0, 1, 1), (0, 1, 3, 1)]),
+        PieceEnum::None => (0, vec![]),
+    };
+
+    let left = center - width*cell/2;
+    cells.iter()
+        .map(|(x, y, w, h)| Rect::new(left + x*cell, top + y*cell, w*cell, h*cell))
+        .collect()
+}
+
+fn draw_preview(piece: &PieceEnum, center: u32, top: u32, cell: u32, color: Color) {
+    for rect in preview_rects(piece, center, top, cell).iter() {
+        let (x, y, w, h) = rect.get_data();
+        draw_rectangle(x, y, w, h, color);
+    }
+}
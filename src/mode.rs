@@ -0,0 +1,73 @@
+use crate::level::LINES_PER_LEVEL;
+
+/// marathon is won by finishing this level, whatever level it started on
+pub const MARATHON_LEVEL: u32 = 15;
+/// line goals offered for sprint
+pub const SPRINT_GOALS: [u32; 3] = [20, 40, 100];
+/// the standard sprint
+pub const SPRINT_DEFAULT: u32 = 40;
+/// time limits offered for ultra, in seconds
+pub const ULTRA_LIMITS: [u32; 3] = [60, 120, 180];
+/// the standard two minute ultra
+pub const ULTRA_DEFAULT: u32 = 120;
+/// garbage row counts offered for dig
+pub const DIG_ROWS: [u32; 3] = [10, 18, 100];
+/// dig never has more garbage rows than this on the board, the rest come in as they get cleared,
+/// boards too short for it show fewer
+pub const DIG_ROWS_SHOWN: u32 = 10;
+/// ticks before survival's first garbage row
+pub const SURVIVAL_START_INTERVAL: u64 = 8*60;
+/// survival rows never come closer together than this many ticks
+pub const SURVIVAL_MIN_INTERVAL: u64 = 60;
+/// a split time gets taken every this many lines
+pub const SPLIT_LINES: u32 = 10;
+
+/// what a game is played for, and so when it ends
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GameMode {
+    /// speeds up every 10 lines and is won by finishing level MARATHON_LEVEL,
+    /// `endless` plays on past that until the stack reaches the top
+    Marathon { endless: bool },
+    /// clear `lines` lines as fast as possible
+    Sprint { lines: u32 },
+    /// score as much as possible before `seconds` run out
+    Ultra { seconds: u32 },
+    /// starts on top of `rows` rows of garbage and is won once they're all cleared
+    Dig { rows: u32 },
+    /// garbage rises from the floor faster and faster until the stack reaches the top
+    Survival,
+}
+
+impl Default for GameMode {
+    fn default() -> Self {
+        Self::Marathon { endless: true }
+    }
+}
+
+impl GameMode {
+    /// the line count the mode is won at, if there is one,
+    /// marathon's depends on how far from MARATHON_LEVEL it started and is never less than a level
+    pub fn line_goal(&self, start_level: u32) -> Option<u32> {
+        match self {
+            Self::Marathon { .. } => {
+                let levels = (MARATHON_LEVEL + 1).saturating_sub(start_level).max(1);
+                Some(levels*LINES_PER_LEVEL)
+            },
+            Self::Sprint { lines } => Some(*lines),
+            Self::Ultra { .. } | Self::Dig { .. } | Self::Survival => None,
+        }
+    }
+
+    /// false when reaching the line goal lets the game carry on
+    pub fn ends_at_goal(&self) -> bool {
+        !matches!(self, Self::Marathon { endless: true })
+    }
+
+    /// seconds of play before the game ends on its own, if there's a limit
+    pub fn time_limit(&self) -> Option<u32> {
+        match self {
+            Self::Ultra { seconds } => Some(*seconds),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use macroquad::prelude::*;
+use tetris::{GameEvent, Replay, ReplayPlayer};
+use tetris::level::{FPS, FRAME_TIME};
+
+use crate::text_helper;
+
+// how far the arrow keys jump, in ticks
+const SEEK_FRAMES: u64 = 5 * FPS as u64;
+const MIN_SPEED: f64 = 0.25;
+const MAX_SPEED: f64 = 8.0;
+
+// a replay being watched, with its own clock so it can be paused and sped up
+pub struct Playback {
+    pub player: ReplayPlayer,
+    pub paused: bool,
+    pub speed: f64,
+    /// events from every step since the last take, a fast replay runs several steps a frame
+    pub events: Vec<GameEvent>,
+    accumulator: f64,
+}
+
+impl Playback {
+    pub fn new(replay: Replay) -> Self {
+        Self {
+            player: ReplayPlayer::new(replay),
+            paused: false,
+            speed: 1.0,
+            events: Vec::new(),
+            accumulator: 0.0,
+        }
+    }
+
+    // None when the file can't be read or isn't a replay
+    #[cfg(not(target_family = "wasm"))]
+    pub fn load(path: &str) -> Option<Self> {
+        let bytes = std::fs::read(path).ok()?;
+        Replay::from_bytes(&bytes).ok().map(Self::new)
+    }
+
+    #[cfg(target_family = "wasm")]
+    pub fn load(_path: &str) -> Option<Self> {
+        None
+    }
+
+    pub fn handle_key(mut self, key: KeyCode) -> Self {
+        match key {
+            KeyCode::P | KeyCode::Space => self.paused = !self.paused,
+            KeyCode::Left => {
+                let frame = self.player.game.frame.saturating_sub(SEEK_FRAMES);
+                self.player = self.player.seek(frame);
+            },
+            KeyCode::Right => {
+                let frame = self.player.game.frame + SEEK_FRAMES;
+                self.player = self.player.seek(frame);
+            },
+            KeyCode::Up => self.speed = (self.speed*2.0).min(MAX_SPEED),
+            KeyCode::Down => self.speed = (self.speed/2.0).max(MIN_SPEED),
+            _ => (),
+        }
+
+        self
+    }
+
+    pub fn update(mut self, dt: f64) -> Self {
+        if self.paused || self.player.is_finished() {
+            return self;
+        }
+
+        // same quarter second cap as live play, scaled so 8x can still keep up
+        self.accumulator = (self.accumulator + dt*self.speed).min(0.25*self.speed);
+        while self.accumulator >= FRAME_TIME && !self.player.is_finished() {
+            self.player = self.player.step();
+            self.events.append(&mut self.player.last_events);
+            self.accumulator -= FRAME_TIME;
+        }
+
+        self
+    }
+
+    pub fn draw_overlay(&self, font: &Font) {
+        let seconds = self.player.game.frame as f64 / FPS;
+        let total = self.player.replay.len() as f64 / FPS;
+
+        // along the bottom, clear of the previews and any sprint splits
+        text_helper(font, 40, 900.0, 1110.0, &format!("REPLAY  {seconds:.1}/{total:.1}"));
+
+        let state = if self.player.is_finished() {
+            "END"
+        } else if self.paused {
+            "PAUSED"
+        } else {
+            ""
+        };
+        text_helper(font, 40, 900.0, 1160.0, &format!("{}X  {state}", self.speed));
+    }
+}
+
+// keeps a copy of every finished game in replays/ under the working directory
+#[cfg(not(target_family = "wasm"))]
+pub fn save(replay: &Replay) {
+    if std::fs::create_dir_all("replays").is_ok() {
+        let _ = std::fs::write(format!("replays/{}.replay", replay.seed), replay.to_bytes());
+    }
+}
+
+#[cfg(target_family = "wasm")]
+pub fn save(_replay: &Replay) {}
@@ -0,0 +1,183 @@
+use std::fmt::Debug;
+
+use crate::piece::PieceEnum;
+use crate::rng::Rng;
+
+/// decides which piece comes next, the game owns the rng so seeding stays in one place
+pub trait Randomizer: Debug {
+    fn next_piece(&mut self, rng: &mut Rng) -> PieceEnum;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum RandomizerKind {
+    #[default]
+    Bag7,
+    Bag14,
+    Random,
+    Tgm,
+    Nes,
+}
+
+impl RandomizerKind {
+    pub fn build(&self) -> Box<dyn Randomizer> {
+        match self {
+            Self::Bag7 => Box::new(Bag::new(1)),
+            Self::Bag14 => Box::new(Bag::new(2)),
+            Self::Random => Box::new(PureRandom),
+            Self::Tgm => Box::new(History::new(6)),
+            Self::Nes => Box::new(Nes::new()),
+        }
+    }
+}
+
+/// deals `copies` of every piece in a shuffled order before refilling
+#[derive(Debug)]
+pub struct Bag {
+    copies: usize,
+    pieces: Vec<PieceEnum>,
+}
+
+impl Bag {
+    pub fn new(copies: usize) -> Self {
+        Self { copies, pieces: Vec::new() }
+    }
+
+    fn refill(&mut self, rng: &mut Rng) {
+        for _ in 0..self.copies {
+            self.pieces.extend(PieceEnum::ALL);
+        }
+
+        // fisher yates, pieces are popped off the end
+        for i in (1..self.pieces.len()).rev() {
+            let j = rng.gen_range(0, i as u32 + 1) as usize;
+            self.pieces.swap(i, j);
+        }
+    }
+}
+
+impl Randomizer for Bag {
+    fn next_piece(&mut self, rng: &mut Rng) -> PieceEnum {
+        if self.pieces.is_empty() {
+            self.refill(rng);
+        }
+        self.pieces.pop().unwrap()
+    }
+}
+
+#[derive(Debug)]
+pub struct PureRandom;
+
+impl Randomizer for PureRandom {
+    fn next_piece(&mut self, rng: &mut Rng) -> PieceEnum {
+        PieceEnum::random_piece(rng)
+    }
+}
+
+/// tgm style, rolls up to `tries` times to avoid anything in the last 4 pieces
+#[derive(Debug)]
+pub struct History {
+    history: [PieceEnum; 4],
+    tries: u32,
+    first: bool,
+}
+
+impl History {
+    pub fn new(tries: u32) -> Self {
+        Self {
+            history: [PieceEnum::Z, PieceEnum::S, PieceEnum::S, PieceEnum::Z],
+            tries,
+            first: true,
+        }
+    }
+}
+
+impl Randomizer for History {
+    fn next_piece(&mut self, rng: &mut Rng) -> PieceEnum {
+        let piece = if self.first {
+            // never start on a piece that forces an overhang
+            self.first = false;
+            let starts = [PieceEnum::I, PieceEnum::T, PieceEnum::J, PieceEnum::L];
+            starts[rng.gen_range(0, 4) as usize]
+        } else {
+            let mut piece = PieceEnum::random_piece(rng);
+            for _ in 1..self.tries {
+                if !self.history.contains(&piece) {
+                    break;
+                }
+                piece = PieceEnum::random_piece(rng);
+            }
+            piece
+        };
+
+        self.history.rotate_right(1);
+        self.history[0] = piece;
+        piece
+    }
+}
+
+/// rolls 8 sides, a repeat or the blank side gets one reroll which is kept no matter what
+#[derive(Debug)]
+pub struct Nes {
+    last: PieceEnum,
+}
+
+impl Nes {
+    pub fn new() -> Self {
+        Self { last: PieceEnum::None }
+    }
+}
+
+impl Default for Nes {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Randomizer for Nes {
+    fn next_piece(&mut self, rng: &mut Rng) -> PieceEnum {
+        let roll = rng.gen_range(0, 8) as usize;
+        let piece = if roll == 7 || PieceEnum::ALL[roll] == self.last {
+            PieceEnum::random_piece(rng)
+        } else {
+            PieceEnum::ALL[roll]
+        };
+
+        self.last = piece;
+        piece
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_handler::Game;
+
+    fn deal(kind: RandomizerKind, seed: u64, count: usize) -> Vec<PieceEnum> {
+        let mut rng = Rng::new(seed);
+        let mut randomizer = kind.build();
+        (0..count).map(|_| randomizer.next_piece(&mut rng)).collect()
+    }
+
+    #[test]
+    fn bags_deal_every_piece_before_repeating() {
+        for (kind, copies) in [(RandomizerKind::Bag7, 1), (RandomizerKind::Bag14, 2)] {
+            let size = 7*copies;
+            for bag in deal(kind, 5, size*20).chunks(size) {
+                for piece in PieceEnum::ALL {
+                    assert_eq!(bag.iter().filter(|&&dealt| dealt == piece).count(), copies);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn the_same_seed_deals_the_same_queue() {
+        for kind in [RandomizerKind::Bag7, RandomizerKind::Bag14, RandomizerKind::Random, RandomizerKind::Tgm, RandomizerKind::Nes] {
+            assert_eq!(deal(kind, 9, 100), deal(kind, 9, 100));
+        }
+        assert_ne!(deal(RandomizerKind::Bag7, 9, 100), deal(RandomizerKind::Bag7, 10, 100));
+
+        let queue = |seed| Game::with_seed(seed).next_pieces().copied().collect::<Vec<_>>();
+        assert_eq!(queue(9), queue(9));
+    }
+}
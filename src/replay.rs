@@ -0,0 +1,491 @@
+use crate::config::{GameConfig, LockReset};
+use crate::event::GameEvent;
+use crate::game_handler::Game;
+use crate::input::{Handling, Inputs};
+use crate::mode::GameMode;
+use crate::randomizer::RandomizerKind;
+use crate::scoring::ScoreTable;
+
+const MAGIC: &[u8; 4] = b"TRPL";
+const VERSION: u8 = 4;
+/// four hours at 60 ticks a second, anything longer is a corrupt or hostile file
+const MAX_TICKS: usize = 4*60*60*60;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReplayError {
+    /// doesn't start with the replay magic, probably not a replay at all
+    BadMagic,
+    UnsupportedVersion(u8),
+    /// ran out of bytes part way through
+    Truncated,
+    /// a value that can't come from a real game, like an unknown randomizer
+    Invalid,
+}
+
+/// seed, config and the buttons held on every tick, that's all it takes to play a game back
+#[derive(Debug, Clone)]
+pub struct Replay {
+    pub seed: u64,
+    pub config: GameConfig,
+    pub inputs: Vec<Inputs>,
+}
+
+impl Replay {
+    /// keeps the normalized config, the one Game::with_config really plays
+    pub fn new(seed: u64, config: GameConfig) -> Self {
+        Self { seed, config: config.normalized(), inputs: Vec::new() }
+    }
+
+    /// call with whatever got passed to Game::tick while the game was still going
+    pub fn record(&mut self, inputs: Inputs) {
+        self.inputs.push(inputs);
+    }
+
+    pub fn len(&self) -> usize {
+        self.inputs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inputs.is_empty()
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Writer(Vec::new());
+        out.0.extend_from_slice(MAGIC);
+        out.u8(VERSION);
+        out.u64(self.seed);
+        // normalized again in case the public field got changed, from_bytes only takes normalized configs
+        write_config(&mut out, &self.config.normalized());
+
+        // inputs barely change tick to tick so they're stored as runs of (buttons, ticks)
+        let mut runs: Vec<(Inputs, u16)> = Vec::new();
+        for &inputs in self.inputs.iter().take(MAX_TICKS) {
+            match runs.last_mut() {
+                Some((last, count)) if *last == inputs && *count < u16::MAX => *count += 1,
+                _ => runs.push((inputs, 1)),
+            }
+        }
+
+        out.u32(runs.len() as u32);
+        for (inputs, count) in runs {
+            out.u16(inputs.0);
+            out.u16(count);
+        }
+
+        out.0
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ReplayError> {
+        let mut input = Reader { bytes, pos: 0 };
+        if input.take(4)? != MAGIC {
+            return Err(ReplayError::BadMagic);
+        }
+        let version = input.u8()?;
+        if version != VERSION {
+            return Err(ReplayError::UnsupportedVersion(version));
+        }
+
+        let seed = input.u64()?;
+        let config = read_config(&mut input)?;
+
+        let mut inputs = Vec::new();
+        for _ in 0..input.u32()? {
+            let buttons = Inputs(input.u16()?);
+            let count = input.u16()? as usize;
+            if inputs.len() + count > MAX_TICKS {
+                return Err(ReplayError::Invalid);
+            }
+            inputs.extend(std::iter::repeat_n(buttons, count));
+        }
+
+        Ok(Self { seed, config, inputs })
+    }
+}
+
+/// steps a game through a replay, seeking backwards starts over from the seed
+#[derive(Debug)]
+pub struct ReplayPlayer {
+    pub replay: Replay,
+    pub game: Game,
+    /// what the last step did, replaced every step so nothing piles up when nobody reads it
+    pub last_events: Vec<GameEvent>,
+}
+
+impl ReplayPlayer {
+    pub fn new(replay: Replay) -> Self {
+        let game = Game::with_config(replay.seed, replay.config);
+        Self { replay, game, last_events: Vec::new() }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.game.is_over() || self.game.frame as usize >= self.replay.len()
+    }
+
+    pub fn step(mut self) -> Self {
+        if let Some(&inputs) = self.replay.inputs.get(self.game.frame as usize) {
+            self.game = self.game.tick(inputs);
+        }
+        self.last_events = self.game.drain_events();
+        self
+    }
+
+    // a jump isn't played, so whatever happened along the way is dropped
+    pub fn seek(mut self, frame: u64) -> Self {
+        if frame < self.game.frame {
+            self.game = Game::with_config(self.replay.seed, self.replay.config);
+        }
+        while self.game.frame < frame && !self.is_finished() {
+            self = self.step();
+        }
+        self.game.drain_events();
+        self.last_events.clear();
+        self
+    }
+}
+
+fn write_config(out: &mut Writer, config: &GameConfig) {
+    match config.mode {
+        GameMode::Marathon { endless } => {
+            out.u8(0);
+            out.u32(!endless as u32);
+        },
+        GameMode::Sprint { lines } => {
+            out.u8(1);
+            out.u32(lines);
+        },
+        GameMode::Ultra { seconds } => {
+            out.u8(2);
+            out.u32(seconds);
+        },
+        GameMode::Dig { rows } => {
+            out.u8(3);
+            out.u32(rows);
+        },
+        GameMode::Survival => {
+            out.u8(4);
+            out.u32(0);
+        },
+    }
+
+    out.u8(match config.randomizer {
+        RandomizerKind::Bag7 => 0,
+        RandomizerKind::Bag14 => 1,
+        RandomizerKind::Random => 2,
+        RandomizerKind::Tgm => 3,
+        RandomizerKind::Nes => 4,
+    });
+    out.u8(config.preview_count as u8);
+
+    let table = &config.scoring;
+    for &points in table.lines.iter()
+        .chain(table.tspin.iter())
+        .chain(table.tspin_mini.iter())
+        .chain(table.perfect_clear.iter())
+    {
+        out.u32(points);
+    }
+    out.u32(table.combo);
+    out.u32(table.back_to_back);
+    out.u32(table.soft_drop);
+    out.u32(table.hard_drop);
+
+    out.u32(config.start_level);
+    out.f64(config.lock_delay);
+    match config.lock_reset {
+        LockReset::Infinite => {
+            out.u8(0);
+            out.u32(0);
+        },
+        LockReset::MoveReset(limit) => {
+            out.u8(1);
+            out.u32(limit);
+        },
+        LockReset::StepReset => {
+            out.u8(2);
+            out.u32(0);
+        },
+    }
+
+    // normalized sizes are at most MAX_BOARD_SIZE so they always fit
+    out.u16(config.width as u16);
+    out.u16(config.visible_height as u16);
+    out.u16(config.buffer_height as u16);
+
+    out.f64(config.handling.das);
+    out.f64(config.handling.arr);
+    out.f64(config.handling.dcd);
+    out.f64(config.handling.sdf);
+
+    out.f64(config.garbage_messiness);
+}
+
+fn read_config(input: &mut Reader) -> Result<GameConfig, ReplayError> {
+    let mode = match (input.u8()?, input.u32()?) {
+        (0, stops) => GameMode::Marathon { endless: stops == 0 },
+        (1, lines) => GameMode::Sprint { lines },
+        (2, seconds) => GameMode::Ultra { seconds },
+        (3, rows) => GameMode::Dig { rows },
+        (4, _) => GameMode::Survival,
+        _ => return Err(ReplayError::Invalid),
+    };
+
+    let randomizer = match input.u8()? {
+        0 => RandomizerKind::Bag7,
+        1 => RandomizerKind::Bag14,
+        2 => RandomizerKind::Random,
+        3 => RandomizerKind::Tgm,
+        4 => RandomizerKind::Nes,
+        _ => return Err(ReplayError::Invalid),
+    };
+    let preview_count = input.u8()? as usize;
+
+    let mut scoring = ScoreTable::GUIDELINE;
+    for points in scoring.lines.iter_mut()
+        .chain(scoring.tspin.iter_mut())
+        .chain(scoring.tspin_mini.iter_mut())
+        .chain(scoring.perfect_clear.iter_mut())
+    {
+        *points = input.u32()?;
+    }
+    scoring.combo = input.u32()?;
+    scoring.back_to_back = input.u32()?;
+    scoring.soft_drop = input.u32()?;
+    scoring.hard_drop = input.u32()?;
+
+    let start_level = input.u32()?;
+    let lock_delay = input.f64()?;
+    let lock_reset = match (input.u8()?, input.u32()?) {
+        (0, _) => LockReset::Infinite,
+        (1, limit) => LockReset::MoveReset(limit),
+        (2, _) => LockReset::StepReset,
+        _ => return Err(ReplayError::Invalid),
+    };
+
+    let width = input.u16()? as usize;
+    let visible_height = input.u16()? as usize;
+    let buffer_height = input.u16()? as usize;
+
+    let handling = Handling {
+        das: input.f64()?,
+        arr: input.f64()?,
+        dcd: input.f64()?,
+        sdf: input.f64()?,
+    };
+
+    let garbage_messiness = input.f64()?;
+
+    let config = GameConfig {
+        mode,
+        randomizer,
+        preview_count,
+        scoring,
+        start_level,
+        lock_delay,
+        lock_reset,
+        width,
+        visible_height,
+        buffer_height,
+        handling,
+        garbage_messiness,
+    };
+
+    // to_bytes only ever writes normalized configs, anything else didn't come from a real game
+    if config != config.normalized() {
+        return Err(ReplayError::Invalid);
+    }
+    Ok(config)
+}
+
+// everything is little endian
+struct Writer(Vec<u8>);
+
+impl Writer {
+    fn u8(&mut self, value: u8) {
+        self.0.push(value);
+    }
+
+    fn u16(&mut self, value: u16) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn u64(&mut self, value: u64) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn f64(&mut self, value: f64) {
+        self.u64(value.to_bits());
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn take(&mut self, len: usize) -> Result<&[u8], ReplayError> {
+        let bytes = self.bytes.get(self.pos..self.pos+len).ok_or(ReplayError::Truncated)?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, ReplayError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, ReplayError> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<u32, ReplayError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, ReplayError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn f64(&mut self) -> Result<f64, ReplayError> {
+        Ok(f64::from_bits(self.u64()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    // a few thousand ticks of buttons that change every handful of ticks, enough to lock and clear
+    fn record_game(seed: u64, config: GameConfig) -> (Replay, Game) {
+        let mut rng = Rng::new(seed);
+        let mut replay = Replay::new(seed, config);
+        let mut game = Game::with_config(seed, config);
+        let mut inputs = Inputs::default();
+
+        while !game.is_over() && game.frame < 5000 {
+            if game.frame.is_multiple_of(6) {
+                inputs = Inputs(rng.gen_range(0, 256) as u16);
+            }
+            replay.record(inputs);
+            game = game.tick(inputs);
+        }
+        (replay, game)
+    }
+
+    fn board_cells(game: &Game) -> Vec<((i32, i32), bool)> {
+        game.board.cells().map(|(location, cell)| (location, cell.garbage)).collect()
+    }
+
+    #[test]
+    fn round_trip_replays_the_same_game() {
+        let config = GameConfig { mode: GameMode::Dig { rows: 10 }, ..Default::default() };
+        let (replay, game) = record_game(7, config);
+        assert!(game.score > 0 && game.frame > 100);
+
+        let loaded = Replay::from_bytes(&replay.to_bytes()).unwrap();
+        assert_eq!(loaded.seed, replay.seed);
+        assert_eq!(loaded.config.mode, config.mode);
+        assert_eq!(loaded.inputs, replay.inputs);
+
+        let mut player = ReplayPlayer::new(loaded);
+        while !player.is_finished() {
+            player = player.step();
+        }
+        assert_eq!(player.game.score, game.score);
+        assert_eq!(player.game.lines, game.lines);
+        assert_eq!(player.game.frame, game.frame);
+        assert_eq!(player.game.game_over, game.game_over);
+        assert_eq!(board_cells(&player.game), board_cells(&game));
+    }
+
+    #[test]
+    fn seeking_back_matches_playing_forward() {
+        let (replay, _) = record_game(3, GameConfig::default());
+        let middle = replay.len() as u64/2;
+        assert!(middle > 100);
+        let forward = ReplayPlayer::new(replay.clone()).seek(middle);
+        let back = ReplayPlayer::new(replay).seek(middle*2).seek(middle);
+
+        assert_eq!(back.game.frame, middle);
+        assert_eq!(back.game.score, forward.game.score);
+        assert_eq!(board_cells(&back.game), board_cells(&forward.game));
+    }
+
+    #[test]
+    fn playing_a_replay_leaves_no_events_behind() {
+        let (replay, _) = record_game(4, GameConfig::default());
+        let mut player = ReplayPlayer::new(replay);
+        let mut seen = 0;
+        while !player.is_finished() {
+            player = player.step();
+            seen += player.last_events.len();
+            assert!(player.game.drain_events().is_empty());
+        }
+        assert!(seen > 0);
+
+        let mut player = player.seek(10);
+        assert!(player.last_events.is_empty());
+        assert!(player.game.drain_events().is_empty());
+    }
+
+    #[test]
+    fn rejects_bad_files() {
+        let bytes = Replay::new(1, GameConfig::default()).to_bytes();
+        assert_eq!(Replay::from_bytes(b"nope").unwrap_err(), ReplayError::BadMagic);
+        assert_eq!(Replay::from_bytes(&bytes[..bytes.len()-1]).unwrap_err(), ReplayError::Truncated);
+
+        let mut newer = bytes.clone();
+        newer[4] = VERSION+1;
+        assert_eq!(Replay::from_bytes(&newer).unwrap_err(), ReplayError::UnsupportedVersion(VERSION+1));
+
+        let narrow = GameConfig { width: 1, ..Default::default() };
+        assert_eq!(Replay::from_bytes(&raw_bytes(&narrow)).unwrap_err(), ReplayError::Invalid);
+
+        // these used to load and then overflow on the first clear
+        let high_level = GameConfig { start_level: u32::MAX, ..Default::default() };
+        assert_eq!(Replay::from_bytes(&raw_bytes(&high_level)).unwrap_err(), ReplayError::Invalid);
+        let mut huge_table = GameConfig::default();
+        huge_table.scoring.lines[1] = u32::MAX;
+        assert_eq!(Replay::from_bytes(&raw_bytes(&huge_table)).unwrap_err(), ReplayError::Invalid);
+    }
+
+    // a replay file with `config` written as is, the way to_bytes never would
+    fn raw_bytes(config: &GameConfig) -> Vec<u8> {
+        let mut out = Writer(Vec::new());
+        out.0.extend_from_slice(MAGIC);
+        out.u8(VERSION);
+        out.u64(1);
+        write_config(&mut out, config);
+        out.u32(0);
+        out.0
+    }
+
+    #[test]
+    fn out_of_range_configs_still_round_trip() {
+        let config = GameConfig { width: 1, visible_height: 0, buffer_height: 5000, preview_count: 0, ..Default::default() };
+        let loaded = Replay::from_bytes(&Replay::new(1, config).to_bytes()).unwrap();
+
+        // what's loaded is what got played
+        assert_eq!(loaded.config, config.normalized());
+        assert_eq!(Game::with_config(1, config).board.width(), loaded.config.width);
+    }
+
+    #[test]
+    fn rejects_runs_past_the_tick_limit() {
+        let mut bytes = Replay::new(1, GameConfig::default()).to_bytes();
+        let runs = MAX_TICKS/u16::MAX as usize + 1;
+
+        // swap the empty run count at the end for enough maxed out runs to go over
+        bytes.truncate(bytes.len()-4);
+        bytes.extend_from_slice(&(runs as u32).to_le_bytes());
+        for _ in 0..runs {
+            bytes.extend_from_slice(&0u16.to_le_bytes());
+            bytes.extend_from_slice(&u16::MAX.to_le_bytes());
+        }
+        assert_eq!(Replay::from_bytes(&bytes).unwrap_err(), ReplayError::Invalid);
+    }
+}
@@ -0,0 +1,38 @@
+// xorshift64*, each game owns one so the same seed always deals the same pieces
+#[derive(Clone, Debug)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // run the seed through splitmix64 so nearby seeds don't start out alike,
+        // and xorshift gets stuck on 0
+        let mut z = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        let state = if z == 0 { 0x2545_f491_4f6c_dd1d } else { z };
+
+        Self { state }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let mut x = self.state;
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        self.state = x;
+        x.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// uniform in 0..1
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// random number in low..high
+    pub fn gen_range(&mut self, low: u32, high: u32) -> u32 {
+        low + (self.next_u64() % (high - low) as u64) as u32
+    }
+}
@@ -0,0 +1,134 @@
+use crate::piece::PieceEnum;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RotateDirection {
+    Clockwise,
+    CounterClockwise,
+    Half,
+}
+
+/// srs rotation states, Spawn is how a piece enters the field
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Rotation {
+    #[default]
+    Spawn,
+    Right,
+    Two,
+    Left,
+}
+
+impl Rotation {
+    pub fn rotate(self, direction: RotateDirection) -> Self {
+        let turns = match direction {
+            RotateDirection::Clockwise => 1,
+            RotateDirection::Half => 2,
+            RotateDirection::CounterClockwise => 3,
+        };
+        Self::from_index((self.index() + turns) % 4)
+    }
+
+    pub fn index(self) -> usize {
+        match self {
+            Self::Spawn => 0,
+            Self::Right => 1,
+            Self::Two => 2,
+            Self::Left => 3,
+        }
+    }
+
+    fn from_index(index: usize) -> Self {
+        match index {
+            0 => Self::Spawn,
+            1 => Self::Right,
+            2 => Self::Two,
+            3 => Self::Left,
+            _ => panic!("rotation index out of range"),
+        }
+    }
+}
+
+// offsets are written y up like the srs tables everyone copies, kicks() flips them for the field
+const JLSTZ_KICKS: [[(i32, i32); 5]; 8] = [
+    [(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)], // 0 -> R
+    [(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)],     // R -> 0
+    [(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)],     // R -> 2
+    [(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)], // 2 -> R
+    [(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)],    // 2 -> L
+    [(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)],  // L -> 2
+    [(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)],  // L -> 0
+    [(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)],    // 0 -> L
+];
+
+const I_KICKS: [[(i32, i32); 5]; 8] = [
+    [(0, 0), (-2, 0), (1, 0), (-2, -1), (1, 2)],   // 0 -> R
+    [(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)],   // R -> 0
+    [(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)],   // R -> 2
+    [(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)],   // 2 -> R
+    [(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)],   // 2 -> L
+    [(0, 0), (-2, 0), (1, 0), (-2, -1), (1, 2)],   // L -> 2
+    [(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)],   // L -> 0
+    [(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)],   // 0 -> L
+];
+
+// srs has no 180 spins, these are the ones tetr.io uses for every piece
+const HALF_KICKS: [[(i32, i32); 6]; 4] = [
+    [(0, 0), (0, 1), (1, 1), (-1, 1), (1, 0), (-1, 0)],    // 0 -> 2
+    [(0, 0), (1, 0), (1, 2), (1, 1), (0, 2), (0, 1)],      // R -> L
+    [(0, 0), (0, -1), (-1, -1), (1, -1), (-1, 0), (1, 0)], // 2 -> 0
+    [(0, 0), (-1, 0), (-1, 2), (-1, 1), (0, 2), (0, 1)],   // L -> R
+];
+
+/// offsets to try in order when rotating `from` -> `to`, in field coordinates (y down)
+pub fn kicks(piece: PieceEnum, from: Rotation, to: Rotation) -> Vec<(i32, i32)> {
+    let table: &[(i32, i32)] = match (from, to) {
+        (Rotation::Spawn, Rotation::Two) => &HALF_KICKS[0],
+        (Rotation::Right, Rotation::Left) => &HALF_KICKS[1],
+        (Rotation::Two, Rotation::Spawn) => &HALF_KICKS[2],
+        (Rotation::Left, Rotation::Right) => &HALF_KICKS[3],
+        _ => {
+            let row = match (from, to) {
+                (Rotation::Spawn, Rotation::Right) => 0,
+                (Rotation::Right, Rotation::Spawn) => 1,
+                (Rotation::Right, Rotation::Two) => 2,
+                (Rotation::Two, Rotation::Right) => 3,
+                (Rotation::Two, Rotation::Left) => 4,
+                (Rotation::Left, Rotation::Two) => 5,
+                (Rotation::Left, Rotation::Spawn) => 6,
+                (Rotation::Spawn, Rotation::Left) => 7,
+                _ => return vec![(0, 0)],
+            };
+            match piece {
+                PieceEnum::I => &I_KICKS[row],
+                PieceEnum::O => return vec![(0, 0)],
+                _ => &JLSTZ_KICKS[row],
+            }
+        },
+    };
+
+    table.iter().map(|&(x, y)| (x, -y)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn i_spawn_to_right_kicks_flip_to_y_down() {
+        let offsets = kicks(PieceEnum::I, Rotation::Spawn, Rotation::Right);
+        assert_eq!(offsets, vec![(0, 0), (-2, 0), (1, 0), (-2, 1), (1, -2)]);
+    }
+
+    #[test]
+    fn each_kick_undoes_the_one_back() {
+        // srs tables mirror, the kicks from R -> 0 are the kicks from 0 -> R the other way round
+        for piece in [PieceEnum::I, PieceEnum::T] {
+            for from in [Rotation::Spawn, Rotation::Right, Rotation::Two, Rotation::Left] {
+                let to = from.rotate(RotateDirection::Clockwise);
+                let there = kicks(piece, from, to);
+                let back: Vec<_> = kicks(piece, to, from).iter().map(|&(x, y)| (-x, -y)).collect();
+                assert_eq!(there, back);
+            }
+        }
+        assert_eq!(kicks(PieceEnum::O, Rotation::Spawn, Rotation::Right), vec![(0, 0)]);
+    }
+}
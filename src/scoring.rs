@@ -0,0 +1,156 @@
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum TSpin {
+    #[default]
+    None,
+    Mini,
+    Full,
+}
+
+/// points for everything that can score, line clear values get multiplied by the level
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScoreTable {
+    /// indexed by lines cleared, 0 to 4
+    pub lines: [u32; 5],
+    /// t-spins clearing 0 to 3 lines
+    pub tspin: [u32; 4],
+    /// mini t-spins clearing 0 to 2 lines
+    pub tspin_mini: [u32; 3],
+    /// added on top of the clear when the board ends up empty, indexed by lines cleared
+    pub perfect_clear: [u32; 5],
+    /// per combo step, so the 3rd clear in a row gets 2 * combo
+    pub combo: u32,
+    /// extra percent for back to back tetrises and t-spins, 0 turns it off
+    pub back_to_back: u32,
+    /// per row, these don't get multiplied by the level
+    pub soft_drop: u32,
+    pub hard_drop: u32,
+}
+
+impl ScoreTable {
+    pub const GUIDELINE: Self = Self {
+        lines: [0, 100, 300, 500, 800],
+        tspin: [400, 800, 1200, 1600],
+        tspin_mini: [100, 200, 400],
+        perfect_clear: [0, 800, 1200, 1800, 2000],
+        combo: 50,
+        back_to_back: 50,
+        soft_drop: 1,
+        hard_drop: 2,
+    };
+
+    // nes has no spins, combos or hard drop and its level 0 is our level 1
+    pub const NES: Self = Self {
+        lines: [0, 40, 100, 300, 1200],
+        tspin: [0, 40, 100, 300],
+        tspin_mini: [0, 40, 100],
+        perfect_clear: [0; 5],
+        combo: 0,
+        back_to_back: 0,
+        soft_drop: 1,
+        hard_drop: 0,
+    };
+}
+
+impl Default for ScoreTable {
+    fn default() -> Self {
+        Self::GUIDELINE
+    }
+}
+
+/// keeps track of combos and back to back across locks
+#[derive(Debug, Clone)]
+pub struct Scorer {
+    pub table: ScoreTable,
+    /// None until something clears, Some(0) for the first clear of a chain
+    pub combo: Option<u32>,
+    pub back_to_back: bool,
+}
+
+impl Scorer {
+    pub fn new(table: ScoreTable) -> Self {
+        Self { table, combo: None, back_to_back: false }
+    }
+
+    /// points for one piece locking, call it even when nothing cleared so combos can break
+    pub fn lock(&mut self, lines: usize, tspin: TSpin, perfect_clear: bool, level: u32) -> u32 {
+        let lines = lines.min(4);
+        // level 0 has the slowest gravity but still scores like level 1
+        let level = level.max(1);
+        let base = match tspin {
+            TSpin::None => self.table.lines[lines],
+            TSpin::Mini => self.table.tspin_mini[lines.min(2)],
+            TSpin::Full => self.table.tspin[lines.min(3)],
+        };
+
+        if lines == 0 {
+            // a spin without lines doesn't break back to back
+            self.combo = None;
+            return base.saturating_mul(level);
+        }
+
+        let difficult = lines == 4 || tspin != TSpin::None;
+        let mut points = base;
+        if difficult && self.back_to_back {
+            points = points.saturating_add(points.saturating_mul(self.table.back_to_back)/100);
+        }
+        self.back_to_back = difficult;
+
+        let combo = self.combo.map_or(0, |combo| combo+1);
+        self.combo = Some(combo);
+        points = points.saturating_add(self.table.combo.saturating_mul(combo));
+
+        if perfect_clear {
+            points = points.saturating_add(self.table.perfect_clear[lines]);
+        }
+
+        // a long enough game tops out at u32::MAX instead of overflowing
+        points.saturating_mul(level)
+    }
+
+    pub fn soft_drop(&self, rows: u32) -> u32 {
+        self.table.soft_drop.saturating_mul(rows)
+    }
+
+    pub fn hard_drop(&self, rows: u32) -> u32 {
+        self.table.hard_drop.saturating_mul(rows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tsd_scores_1200_and_back_to_back_adds_half() {
+        let mut scorer = Scorer::new(ScoreTable::GUIDELINE);
+        assert_eq!(scorer.lock(2, TSpin::Full, false, 1), 1200);
+
+        // the second clear in a row is also a combo step
+        assert_eq!(scorer.lock(4, TSpin::None, false, 1), 800 + 400 + 50);
+        assert_eq!(scorer.lock(1, TSpin::None, false, 2), (100 + 2*50)*2);
+        assert!(!scorer.back_to_back);
+    }
+
+    #[test]
+    fn a_lock_without_lines_breaks_the_combo_but_not_back_to_back() {
+        let mut scorer = Scorer::new(ScoreTable::GUIDELINE);
+        scorer.lock(4, TSpin::None, false, 1);
+        assert_eq!(scorer.lock(0, TSpin::None, false, 1), 0);
+        assert_eq!(scorer.combo, None);
+        assert_eq!(scorer.lock(4, TSpin::None, false, 1), 1200);
+    }
+
+    #[test]
+    fn perfect_clears_add_on_top_of_the_clear() {
+        let mut scorer = Scorer::new(ScoreTable::GUIDELINE);
+        assert_eq!(scorer.lock(4, TSpin::None, true, 1), 800 + 2000);
+    }
+
+    #[test]
+    fn nes_tetris_scores_1200_at_level_zero() {
+        let mut scorer = Scorer::new(ScoreTable::NES);
+        assert_eq!(scorer.lock(4, TSpin::None, false, 0), 1200);
+        assert_eq!(scorer.lock(4, TSpin::None, false, 0), 1200);
+        assert_eq!(scorer.hard_drop(20), 0);
+    }
+}
@@ -0,0 +1,35 @@
+use crate::event::GameEvent;
+use crate::scoring::TSpin;
+
+/// running totals built from the event stream
+#[derive(Debug, Clone, Default)]
+pub struct Stats {
+    pub pieces: u32,
+    pub holds: u32,
+    /// indexed by lines cleared at once
+    pub clears: [u32; 5],
+    pub tspins: u32,
+    pub kicks: u32,
+    pub perfect_clears: u32,
+    /// garbage rows cleared
+    pub dug: u32,
+}
+
+impl Stats {
+    pub fn record(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::Locked(_) => self.pieces += 1,
+            GameEvent::HoldUsed(_) => self.holds += 1,
+            GameEvent::Kicked { .. } => self.kicks += 1,
+            GameEvent::TSpin(tspin) if *tspin != TSpin::None => self.tspins += 1,
+            GameEvent::LinesCleared(clear) => {
+                self.clears[clear.lines().min(4)] += 1;
+                self.dug += clear.garbage as u32;
+                if clear.perfect_clear {
+                    self.perfect_clears += 1;
+                }
+            },
+            _ => (),
+        }
+    }
+}